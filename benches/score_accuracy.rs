@@ -0,0 +1,137 @@
+//! Compare how often each plaintext scorer picks the right single-byte XOR key on short inputs.
+//!
+//! Run with `cargo bench --bench score_accuracy`. The inputs are chunks of the plaintext of
+//! challenge 6, which none of the models were built from, each encrypted under a different key
+//! byte; and the lines of challenge 4, 60 hex characters or 30 bytes each, exactly one of which
+//! is English under a single-byte key.
+//!
+//! Every scorer ranks that line first, so 4.txt alone can't tell them apart. The difference is in
+//! the shortest chunks, where a handful of letters say little about unigram frequencies but
+//! plenty about which pairs and triples are plausible; the last lines give each n-gram model's
+//! gain over unigram there.
+use arse::encode::base64::TryFromBase64;
+use arse::encode::hex::TryFromHex;
+use arse::stat::{find_best_in, Histogram, NgramModel};
+use arse::xor_cipher::{score_byte_decode, PlaintextScorer};
+
+/// The key byte that scores best under `scorer`, and its score.
+fn best_key<S: PlaintextScorer>(ciphertext: &[u8], scorer: &S) -> Option<(u8, f64)> {
+    find_best_in(
+        (0..=255u8).filter_map(|b| {
+            score_byte_decode(b, ciphertext, scorer)
+                .ok()
+                .map(|score| (b, score))
+        }),
+        |&(_, score)| Some(score),
+        f64::lt,
+    )
+}
+
+/// The fraction of `chunk_len`-byte chunks of `plaintext` whose key `scorer` recovers.
+fn key_accuracy<S: PlaintextScorer>(plaintext: &[u8], chunk_len: usize, scorer: &S) -> f64 {
+    let chunks = plaintext.chunks_exact(chunk_len).collect::<Vec<_>>();
+    let correct = chunks
+        .iter()
+        .enumerate()
+        .filter(|(i, chunk)| {
+            let key = (i * 97 + 13) as u8;
+            let ciphertext = chunk.iter().map(|b| b ^ key).collect::<Vec<_>>();
+            best_key(&ciphertext, scorer).map(|(b, _)| b) == Some(key)
+        })
+        .count();
+    correct as f64 / chunks.len() as f64
+}
+
+/// Where the English line of challenge 4 ranks among all the lines under `scorer`, from 1.
+fn detection_rank<S: PlaintextScorer>(lines: &[Vec<u8>], answer: usize, scorer: &S) -> usize {
+    let scores = lines
+        .iter()
+        .map(|line| best_key(line, scorer).map_or(f64::MAX, |(_, score)| score))
+        .collect::<Vec<_>>();
+    1 + scores.iter().filter(|&&s| s < scores[answer]).count()
+}
+
+/// The chunk lengths to measure key accuracy at, the last the same as a line of 4.txt.
+const LENGTHS: [usize; 3] = [8, 16, 30];
+
+/// Print a row of the table for `scorer`, and return its key accuracy at each of `LENGTHS`.
+fn report<S: PlaintextScorer>(
+    name: &str,
+    scorer: &S,
+    plaintext: &[u8],
+    lines: &[Vec<u8>],
+    answer: usize,
+) -> Vec<f64> {
+    let accuracies = LENGTHS
+        .iter()
+        .map(|&len| 100.0 * key_accuracy(plaintext, len, scorer))
+        .collect::<Vec<_>>();
+    print!("{:<10}", name);
+    for accuracy in &accuracies {
+        print!(" {:>7.1}%", accuracy);
+    }
+    println!(" {:>10}", detection_rank(lines, answer, scorer));
+    accuracies
+}
+
+fn main() {
+    let mut ciphertext = include_str!("../tests/data/6.txt").to_string();
+    ciphertext.retain(|c| !c.is_whitespace());
+    let plaintext = ciphertext
+        .try_from_base64()
+        .unwrap()
+        .iter()
+        .zip(b"Terminator X: Bring the noise".iter().cycle())
+        .map(|(c, k)| c ^ k)
+        .collect::<Vec<_>>();
+    let lines = include_str!("../tests/data/4.txt")
+        .lines()
+        .map(|line| line.try_from_hex().unwrap())
+        .collect::<Vec<_>>();
+    let answer = lines
+        .iter()
+        .position(|line| {
+            (0..=255u8).any(|b| {
+                line.iter().map(|c| c ^ b).collect::<Vec<_>>() == b"Now that the party is jumping\n"
+            })
+        })
+        .unwrap();
+
+    println!("key recovery accuracy by input length, and rank of the English line in 4.txt");
+    println!(
+        "{:<10} {:>8} {:>8} {:>8} {:>10}",
+        "scorer", "8", "16", "30", "4.txt rank"
+    );
+    let unigram = report("unigram", &Histogram::english(), &plaintext, &lines, answer);
+    report(
+        "bytes",
+        &Histogram::english_bytes(),
+        &plaintext,
+        &lines,
+        answer,
+    );
+    let bigram = report(
+        "bigram",
+        &NgramModel::english(2),
+        &plaintext,
+        &lines,
+        answer,
+    );
+    let trigram = report(
+        "trigram",
+        &NgramModel::english(3),
+        &plaintext,
+        &lines,
+        answer,
+    );
+
+    println!();
+    for (name, accuracies) in &[("bigram", &bigram), ("trigram", &trigram)] {
+        println!(
+            "{} over unigram: {:+.1} points at 8 bytes, {:+.1} at 16",
+            name,
+            accuracies[0] - unigram[0],
+            accuracies[1] - unigram[1]
+        );
+    }
+}
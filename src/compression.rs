@@ -0,0 +1,130 @@
+/// A compression-ratio side channel, in the style of CRIME.
+///
+/// When attacker-controlled data is compressed alongside a secret before encryption, the length
+/// of the ciphertext reveals how much the two have in common: a guess which repeats part of the
+/// secret compresses better than one which doesn't.
+use crate::encode::base64::BASE64_MAP;
+use crate::encrypt::{cbc_encrypt_with_iv, ctr_apply, BLOCK_SIZE};
+use crate::transform::pkcs7_pad_blocks;
+use miniz_oxide::deflate::compress_to_vec;
+use rand::{thread_rng, Rng, RngCore};
+
+/// The cipher used to encrypt compressed requests.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// A stream cipher; the ciphertext is exactly as long as the compressed request.
+    Ctr,
+    /// A block cipher; the ciphertext length is rounded up to a whole number of blocks.
+    Cbc,
+}
+
+/// Format an HTTP-style request carrying `session_id` in a cookie, with `body` as its content.
+pub fn format_request(session_id: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut request = b"POST / HTTP/1.1\nHost: hapless.com\nCookie: sessionid=".to_vec();
+    request.extend_from_slice(session_id);
+    request.extend(format!("\nContent-Length: {}\n", body.len()).into_bytes());
+    request.extend_from_slice(body);
+    request
+}
+
+/// A client which, given a request body, formats, compresses and encrypts a request under a fresh
+/// random key, and reveals only the length of the result.
+pub struct CompressionOracle {
+    session_id: Vec<u8>,
+    mode: Mode,
+}
+
+impl CompressionOracle {
+    /// Create an oracle which includes `session_id` in every request and encrypts with `mode`.
+    pub fn new(session_id: &[u8], mode: Mode) -> Self {
+        CompressionOracle {
+            session_id: session_id.to_vec(),
+            mode,
+        }
+    }
+
+    /// The length of the encrypted, compressed request carrying `body`.
+    pub fn length(&self, body: &[u8]) -> usize {
+        let compressed = compress_to_vec(&format_request(&self.session_id, body), 6);
+
+        let mut rng = thread_rng();
+        let mut key = [0u8; BLOCK_SIZE];
+        rng.fill_bytes(&mut key);
+
+        let ciphertext = match self.mode {
+            Mode::Ctr => ctr_apply(&key, rng.gen(), &compressed),
+            Mode::Cbc => {
+                let mut iv = [0u8; BLOCK_SIZE];
+                rng.fill_bytes(&mut iv);
+                let padded = pkcs7_pad_blocks(compressed, BLOCK_SIZE)
+                    .expect("the block size is always a valid padding length");
+                cbc_encrypt_with_iv(&key, &iv, &padded)
+            }
+        };
+        ciphertext.expect("a fresh key is always valid").len()
+    }
+}
+
+/// The most filler placed in front of a guess. Each filler byte is a distinct literal which costs
+/// the compressor less than a byte, so this is enough to walk the request across several block
+/// boundaries.
+const MAX_FILLER: usize = 48;
+
+/// How many of the most recently recovered characters are repeated in each guess.
+///
+/// DEFLATE codes match lengths in bands which share a Huffman symbol (19 to 22 bytes, for
+/// instance), so a guess which extends a match from 20 to 21 bytes saves a whole literal without
+/// introducing a new length code. Guessing with the whole known prefix instead lets the match
+/// length wander across band boundaries, and the extra symbol can cost more than the literal it
+/// saves.
+const WINDOW: usize = 20;
+
+/// Score a guess by the total length of the requests carrying it after each length of filler.
+///
+/// The filler is drawn from bytes which can't appear in a base64 session id, so it gives the
+/// compressor nothing to match. With a stream cipher it mostly just shakes out Huffman coding
+/// noise; with a block cipher it is what lets a saving show up at all, by sliding the request
+/// across block boundaries a fraction of a byte at a time.
+fn score<O>(oracle: &mut O, guess: &[u8]) -> usize
+where
+    O: FnMut(&[u8]) -> usize,
+{
+    (0..=MAX_FILLER)
+        .map(|pad| {
+            let mut body = (0x80..=0xFF).take(pad).collect::<Vec<u8>>();
+            body.extend_from_slice(guess);
+            oracle(&body)
+        })
+        .sum()
+}
+
+/// Recover the base64 value following `prefix` in requests made through `oracle`, one character
+/// at a time.
+///
+/// Each candidate extension (from the base64 alphabet, or a newline marking the end of the value)
+/// is appended to the last few known characters, and the one whose requests compress best wins.
+/// Returns `None` if the end of the value hasn't been found after `max_len` characters.
+pub fn recover_secret<O>(mut oracle: O, prefix: &[u8], max_len: usize) -> Option<Vec<u8>>
+where
+    O: FnMut(&[u8]) -> usize,
+{
+    let mut known = prefix.to_vec();
+    for _ in 0..max_len {
+        let tail = &known[known.len().saturating_sub(WINDOW)..];
+        let best = BASE64_MAP
+            .iter()
+            .cloned()
+            .chain(Some(b'\n'))
+            .min_by_key(|&c| {
+                let mut guess = tail.to_vec();
+                guess.push(c);
+                score(&mut oracle, &guess)
+            })?;
+
+        if best == b'\n' {
+            return Some(known[prefix.len()..].to_vec());
+        }
+        known.push(best);
+    }
+    None
+}
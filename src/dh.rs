@@ -0,0 +1,132 @@
+/// Finite-field Diffie-Hellman in a prime-order subgroup, and attacks on implementations which
+/// don't check that the other side's public key really lies in it.
+use crate::kangaroo::AbelianGroup;
+use crate::math::rand_range;
+use num_bigint::BigUint;
+use num_traits::{Num, One};
+
+pub mod small_subgroup;
+
+/// A group for Diffie-Hellman: the subgroup of order `q` generated by `g` modulo the prime `p`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Group {
+    pub p: BigUint,
+    pub g: BigUint,
+    pub q: BigUint,
+}
+
+impl Group {
+    /// The group from challenge 57, whose cofactor `(p - 1) / q` has plenty of small factors.
+    ///
+    /// The generator is `2^((p - 1) / q) mod p`.
+    pub fn challenge57() -> Self {
+        Group {
+            p: BigUint::from_str_radix(
+                "7199773997391911030609999317773941274322764333428698921736339643928346453700085358\
+                 802973900485592910475480089726140708102474957429903531369589969318716771",
+                10,
+            )
+            .unwrap(),
+            g: BigUint::from_str_radix(
+                "3468524689967230898527177237540715103948068745088745903720636618983198280760149838\
+                 094349473414709612406268524642135217063834088947293583674093808350147286",
+                10,
+            )
+            .unwrap(),
+            q: BigUint::from_str_radix("236234353446506858198510045061214171961", 10).unwrap(),
+        }
+    }
+
+    /// The group from challenge 58, whose cofactor has small factors, but not enough of them to
+    /// make up the whole of `q`.
+    pub fn challenge58() -> Self {
+        Group {
+            p: BigUint::from_str_radix(
+                "1147037487492527565811666350723216140208665025845389627453499167689899926264158151\
+                 9101074740642369848233294239851519212341844337347119899874391456329785623",
+                10,
+            )
+            .unwrap(),
+            g: BigUint::from_str_radix(
+                "6229523353339612969781592660847410858898813587384599399782901799360636355667402585\
+                 55167783009058567397963466103140082647486611657350811560630587013183357",
+                10,
+            )
+            .unwrap(),
+            q: BigUint::from_str_radix("335062023296420808191071248367701059461", 10).unwrap(),
+        }
+    }
+
+    /// The cofactor `(p - 1) / q`.
+    pub fn cofactor(&self) -> BigUint {
+        (&self.p - 1u32) / &self.q
+    }
+
+    /// Generate a random private key in `[1, q)`.
+    pub fn generate_private(&self) -> BigUint {
+        rand_range(&BigUint::one(), &self.q)
+    }
+
+    /// The public key `g^x mod p` for private key `x`.
+    pub fn public(&self, x: &BigUint) -> BigUint {
+        self.g.modpow(x, &self.p)
+    }
+
+    /// The shared secret `their_public^x mod p`. Nothing checks that `their_public` is in the
+    /// subgroup, which is exactly the mistake `small_subgroup` takes advantage of.
+    pub fn shared_secret(&self, x: &BigUint, their_public: &BigUint) -> BigUint {
+        their_public.modpow(x, &self.p)
+    }
+}
+
+/// A group Diffie-Hellman can be done in: one with a generator of known order, whose shared
+/// secrets can key a MAC.
+pub trait DhGroup: AbelianGroup {
+    /// The generator public keys are powers of.
+    fn generator(&self) -> &Self::Element;
+
+    /// The order of the generator.
+    fn order(&self) -> &BigUint;
+
+    /// The key for MACs under the shared secret `k`.
+    fn mac_key(&self, k: &Self::Element) -> Vec<u8>;
+}
+
+/// Multiplication modulo `p`.
+impl AbelianGroup for Group {
+    type Element = BigUint;
+
+    fn identity(&self) -> BigUint {
+        BigUint::one()
+    }
+
+    fn combine(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * b % &self.p
+    }
+
+    fn scale(&self, a: &BigUint, k: &BigUint) -> BigUint {
+        a.modpow(k, &self.p)
+    }
+
+    fn fingerprint(&self, a: &BigUint) -> u64 {
+        let mut low = [0u8; 8];
+        for (l, b) in low.iter_mut().zip(a.to_bytes_le()) {
+            *l = b;
+        }
+        u64::from_le_bytes(low)
+    }
+}
+
+impl DhGroup for Group {
+    fn generator(&self) -> &BigUint {
+        &self.g
+    }
+
+    fn order(&self) -> &BigUint {
+        &self.q
+    }
+
+    fn mac_key(&self, k: &BigUint) -> Vec<u8> {
+        k.to_bytes_be()
+    }
+}
@@ -0,0 +1,204 @@
+/// Lim and Lee's small-subgroup confinement attack.
+///
+/// If `p - 1 = q * j` and `j` has small factors `r`, then there are elements of order `r` mod `p`.
+/// A party which accepts one of these as a public key computes a shared secret which can only
+/// take `r` different values, and anything it authenticates with that secret gives away its
+/// private key modulo `r`. Enough of those residues pin down the whole key by the Chinese
+/// remainder theorem.
+///
+/// Nothing about that is particular to integers mod `p`, so the attack is written for any
+/// `DhGroup`, given a source of elements of small order; `ec::invalid_curve` runs it on elliptic
+/// curves.
+use super::{DhGroup, Group};
+use crate::hmac::hmac_sha256;
+use crate::kangaroo::catch_with_residue;
+use crate::math::{crt, rand_range, small_factors};
+use num_bigint::BigUint;
+use num_traits::One;
+use std::fmt;
+use std::iter::successors;
+
+/// The message Bob authenticates for anyone who sends him a public key.
+pub const MESSAGE: &[u8] = b"crazy flamboyant for the rap enjoyment";
+
+/// The largest prime factor of a group's order worth brute-forcing a residue for.
+pub const FACTOR_BOUND: u32 = 1 << 16;
+
+/// Bob's reply to a public key, given the MAC key derived from the shared secret: `MESSAGE` and
+/// its MAC.
+pub fn authenticate(key: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    (MESSAGE.to_vec(), hmac_sha256(key, MESSAGE))
+}
+
+/// A party who, given any public key `h`, derives `K = h^x` and sends back a message with its MAC
+/// under `K`, without checking that `h` is in the right subgroup.
+pub struct Bob<G> {
+    group: G,
+    x: BigUint,
+}
+
+impl<G: DhGroup> Bob<G> {
+    /// Create Bob with private key `x` in `group`.
+    pub fn new(group: G, x: BigUint) -> Self {
+        Bob { group, x }
+    }
+
+    /// Bob's public key.
+    pub fn public_key(&self) -> G::Element {
+        self.group.scale(self.group.generator(), &self.x)
+    }
+
+    /// Respond to public key `h` with `(message, MAC)`.
+    pub fn respond(&self, h: &G::Element) -> (Vec<u8>, Vec<u8>) {
+        let k = self.group.scale(h, &self.x);
+        authenticate(&self.group.mac_key(&k))
+    }
+}
+
+/// Find a random element of order `r` mod `p`, where `r` is a prime dividing `p - 1`.
+pub fn element_of_order(group: &Group, r: u32) -> BigUint {
+    let exponent = (&group.p - 1u32) / r;
+    loop {
+        let h = rand_range(&BigUint::from(2u32), &group.p).modpow(&exponent, &group.p);
+        if !h.is_one() {
+            return h;
+        }
+    }
+}
+
+/// An element of order `r` for each small prime factor `r` of the cofactor, found as needed.
+pub fn small_order_elements(group: &Group) -> impl Iterator<Item = (u32, BigUint)> + '_ {
+    small_factors(&group.cofactor(), FACTOR_BOUND)
+        .into_iter()
+        .map(move |r| (r, element_of_order(group, r)))
+}
+
+/// A private key recovered modulo a small prime.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Residue {
+    pub modulus: u32,
+    pub value: u32,
+    /// The number of candidate shared secrets tried before the MAC matched.
+    pub guesses: u32,
+}
+
+impl fmt::Display for Residue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "modulus={} residue={} guesses={}",
+            self.modulus, self.value, self.guesses
+        )
+    }
+}
+
+/// Find which private key modulo `r` produced `mac` over `message`, given the MAC keys for the
+/// shared secrets of keys `0, 1, 2, ...` in turn.
+pub fn match_residue<I>(r: u32, keys: I, message: &[u8], mac: &[u8]) -> Option<Residue>
+where
+    I: IntoIterator<Item = Vec<u8>>,
+{
+    keys.into_iter()
+        .position(|key| hmac_sha256(&key, message) == mac)
+        .map(|b| Residue {
+            modulus: r,
+            value: b as u32,
+            guesses: b as u32 + 1,
+        })
+}
+
+/// Recover the private key behind `oracle` modulo the prime `r`, by sending it `h`, an element of
+/// order `r`, and trying each of the `r` possible shared secrets against the MAC it returns.
+pub fn recover_residue<G, O>(group: &G, r: u32, h: &G::Element, oracle: &mut O) -> Option<Residue>
+where
+    G: DhGroup,
+    O: FnMut(&G::Element) -> (Vec<u8>, Vec<u8>),
+{
+    let (message, mac) = oracle(h);
+    let secrets =
+        successors(Some(group.identity()), |k| Some(group.combine(k, h))).take(r as usize);
+    match_residue(r, secrets.map(|k| group.mac_key(&k)), &message, &mac)
+}
+
+/// Recover residues of the private key behind `oracle` from each `(r, h)` of `elements`, with `h`
+/// of prime order `r`, passing each to `log` as it's found. Moduli already covered are skipped,
+/// and no more elements are taken once the product of the moduli exceeds the group's order.
+pub fn recover_residues<G, E, O, L>(
+    group: &G,
+    elements: E,
+    mut oracle: O,
+    mut log: L,
+) -> Vec<Residue>
+where
+    G: DhGroup,
+    E: IntoIterator<Item = (u32, G::Element)>,
+    O: FnMut(&G::Element) -> (Vec<u8>, Vec<u8>),
+    L: FnMut(&Residue),
+{
+    let mut residues: Vec<Residue> = Vec::new();
+    let mut product = BigUint::one();
+    for (r, h) in elements {
+        if residues.iter().any(|residue| residue.modulus == r) {
+            continue;
+        }
+        if let Some(residue) = recover_residue(group, r, &h, &mut oracle) {
+            log(&residue);
+            product *= r;
+            residues.push(residue);
+        }
+        if &product > group.order() {
+            break;
+        }
+    }
+    residues
+}
+
+/// Combine residues with the Chinese remainder theorem into `(x mod M, M)`.
+pub fn combine(residues: &[Residue]) -> Option<(BigUint, BigUint)> {
+    let pairs = residues
+        .iter()
+        .map(|r| (BigUint::from(r.value), BigUint::from(r.modulus)))
+        .collect::<Vec<_>>();
+    crt(&pairs)
+}
+
+/// Recover the whole private key behind `oracle` from the residues `elements` give away, or
+/// `None` if their orders don't multiply up to more than the group's order.
+pub fn recover_key<G, E, O, L>(group: &G, elements: E, oracle: O, log: L) -> Option<BigUint>
+where
+    G: DhGroup,
+    E: IntoIterator<Item = (u32, G::Element)>,
+    O: FnMut(&G::Element) -> (Vec<u8>, Vec<u8>),
+    L: FnMut(&Residue),
+{
+    let (x, modulus) = combine(&recover_residues(group, elements, oracle, log))?;
+    if &modulus > group.order() {
+        Some(x)
+    } else {
+        None
+    }
+}
+
+/// Recover the whole private key behind `oracle`, whose public key is `y`, using the kangaroo
+/// algorithm to find whatever part of it the residues don't pin down.
+pub fn recover_key_with_kangaroo<O, L>(
+    group: &Group,
+    y: &BigUint,
+    oracle: O,
+    log: L,
+) -> Option<BigUint>
+where
+    O: FnMut(&BigUint) -> (Vec<u8>, Vec<u8>),
+    L: FnMut(&Residue),
+{
+    let (n, r) = combine(&recover_residues(
+        group,
+        small_order_elements(group),
+        oracle,
+        log,
+    ))?;
+    if r > group.q {
+        return Some(n);
+    }
+    catch_with_residue(group, &group.g, y, &n, &r, &group.q)
+}
@@ -0,0 +1,375 @@
+/// The Digital Signature Algorithm, and attacks on careless choices of nonce.
+use crate::math::{invmod, rand_range};
+use num_bigint::BigUint;
+use num_traits::{Num, One, Zero};
+use sha1::{Digest, Sha1};
+
+/// DSA domain parameters shared between signers.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Params {
+    pub p: BigUint,
+    pub q: BigUint,
+    pub g: BigUint,
+}
+
+impl Params {
+    /// The 1024-bit parameters used throughout the Cryptopals DSA challenges.
+    pub fn cryptopals() -> Self {
+        Params {
+            p: BigUint::from_str_radix(
+                "800000000000000089e1855218a0e7dac38136ffafa72eda7\
+                 859f2171e25e65eac698c1702578b07dc2a1076da241c76c6\
+                 2d374d8389ea5aeffd3226a0530cc565f3bf6b50929139ebe\
+                 ac04f48c3c84afb796d61e5a4f9a8fda812ab59494232c7d2\
+                 b4deb50aa18ee9e132bfa85ac4374d7f9091abc3d015efc87\
+                 1a584471bb1",
+                16,
+            )
+            .unwrap(),
+            q: BigUint::from_str_radix("f4f47f05794b256174bba6e9b396a7707e563c5b", 16).unwrap(),
+            g: BigUint::from_str_radix(
+                "5958c9d3898b224b12672c0b98e06c60df923cb8bc999d119\
+                 458fef538b8fa4046c8db53039db620c094c9fa077ef389b5\
+                 322a559946a71903f990f1f7e0e025e2d7f7cf494aff1a047\
+                 0f5b64c36b625a097f1651fe775323556fe00b3608c887892\
+                 878480e99041be601a62166ca6894bdd41a7054ec89f756ba\
+                 9fc95302291",
+                16,
+            )
+            .unwrap(),
+        }
+    }
+
+    /// Copy these parameters, substituting the generator `g`.
+    ///
+    /// This is how a malicious party might tamper with the parameters; values like `0` or `p + 1`
+    /// produce keys for which signatures can be forged.
+    pub fn with_generator(&self, g: BigUint) -> Self {
+        Params { g, ..self.clone() }
+    }
+}
+
+/// How carefully to check signature components before doing any arithmetic with them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Strictness {
+    /// Require `0 < r < q` and `0 < s < q`, as the standard demands, and refuse to work with a
+    /// generator outside `1 < g < p`.
+    Strict,
+    /// Accept (or produce) any values at all, as a careless implementation might.
+    Lax,
+}
+
+impl Strictness {
+    fn accepts_params(self, params: &Params) -> bool {
+        let Params { p, g, .. } = params;
+        match self {
+            Strictness::Strict => g > &BigUint::one() && g < p,
+            Strictness::Lax => true,
+        }
+    }
+
+    fn accepts(self, params: &Params, sig: &Signature) -> bool {
+        let q = &params.q;
+        match self {
+            Strictness::Strict => {
+                self.accepts_params(params)
+                    && !sig.r.is_zero()
+                    && &sig.r < q
+                    && !sig.s.is_zero()
+                    && &sig.s < q
+            }
+            Strictness::Lax => true,
+        }
+    }
+}
+
+/// A DSA public key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PublicKey {
+    pub params: Params,
+    pub y: BigUint,
+}
+
+/// A DSA private key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrivateKey {
+    pub params: Params,
+    pub x: BigUint,
+}
+
+/// A DSA signature.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Signature {
+    pub r: BigUint,
+    pub s: BigUint,
+}
+
+/// Hash `message` with SHA-1 and interpret the digest as an integer.
+pub fn hash_message(message: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(&Sha1::digest(message))
+}
+
+/// Generate a random keypair using `params`.
+pub fn generate_keypair(params: &Params) -> (PublicKey, PrivateKey) {
+    let x = rand_range(&BigUint::one(), &params.q);
+    let private = PrivateKey {
+        params: params.clone(),
+        x,
+    };
+    (private.public_key(), private)
+}
+
+impl PrivateKey {
+    /// Derive the public key `y = g^x mod p`.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey {
+            params: self.params.clone(),
+            y: self.params.g.modpow(&self.x, &self.params.p),
+        }
+    }
+
+    /// Sign `message` with a fresh random nonce.
+    ///
+    /// Panics if the generator is outside `1 < g < p`; use `sign_with` to handle that.
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        self.sign_with(message, Strictness::Strict)
+            .expect("generator must be between 1 and p")
+    }
+
+    /// Sign `message` with a fresh random nonce, checking the resulting signature according to
+    /// `strictness`. A `Lax` signer will happily emit `r = 0`; a `Strict` one returns `None` if
+    /// the generator is outside `1 < g < p`, since no nonce would give an acceptable signature.
+    pub fn sign_with(&self, message: &[u8], strictness: Strictness) -> Option<Signature> {
+        if !strictness.accepts_params(&self.params) {
+            return None;
+        }
+
+        let h = hash_message(message);
+        loop {
+            let k = rand_range(&BigUint::one(), &self.params.q);
+            if let Some(sig) = self.sign_with_nonce_checked(&h, &k, strictness) {
+                return Some(sig);
+            }
+        }
+    }
+
+    /// Sign the message hash `h` using the nonce `k`. Returns `None` if the nonce produces a
+    /// signature with a zero component, in which case a different nonce must be chosen.
+    pub fn sign_with_nonce(&self, h: &BigUint, k: &BigUint) -> Option<Signature> {
+        self.sign_with_nonce_checked(h, k, Strictness::Strict)
+    }
+
+    fn sign_with_nonce_checked(
+        &self,
+        h: &BigUint,
+        k: &BigUint,
+        strictness: Strictness,
+    ) -> Option<Signature> {
+        let Params { p, q, g } = &self.params;
+        let r = g.modpow(k, p) % q;
+        let s = invmod(k, q)? * (h + &self.x * &r) % q;
+        let sig = Signature { r, s };
+        if strictness.accepts(&self.params, &sig) {
+            Some(sig)
+        } else {
+            None
+        }
+    }
+}
+
+impl PublicKey {
+    /// Verify that `sig` is a valid signature over `message`.
+    pub fn verify(&self, message: &[u8], sig: &Signature) -> bool {
+        self.verify_hash(&hash_message(message), sig)
+    }
+
+    /// Verify that `sig` is a valid signature over `message`, checking its components according
+    /// to `strictness`.
+    pub fn verify_with(&self, message: &[u8], sig: &Signature, strictness: Strictness) -> bool {
+        self.verify_hash_with(&hash_message(message), sig, strictness)
+    }
+
+    /// Verify that `sig` is a valid signature over the message hash `h`.
+    pub fn verify_hash(&self, h: &BigUint, sig: &Signature) -> bool {
+        self.verify_hash_with(h, sig, Strictness::Strict)
+    }
+
+    /// Verify that `sig` is a valid signature over the message hash `h`, checking its components
+    /// according to `strictness`.
+    pub fn verify_hash_with(&self, h: &BigUint, sig: &Signature, strictness: Strictness) -> bool {
+        let Params { p, q, g } = &self.params;
+        if !strictness.accepts(&self.params, sig) {
+            return false;
+        }
+
+        let w = match invmod(&sig.s, q) {
+            Some(w) => w,
+            None => return false,
+        };
+        let u1 = h * &w % q;
+        let u2 = &sig.r * &w % q;
+        let v = g.modpow(&u1, p) * self.y.modpow(&u2, p) % p % q;
+        v == sig.r
+    }
+}
+
+/// Produce a signature which verifies for any message at all under a key whose generator was
+/// tampered to `p + 1`, using the arbitrary value `z`:
+///
+/// ```text
+/// r = (y^z mod p) mod q
+/// s = r / z mod q
+/// ```
+///
+/// With `g = 1 (mod p)`, verification reduces to checking `y^(r/s) = y^z`, which holds by
+/// construction regardless of the message hash.
+pub fn magic_signature(public: &PublicKey, z: &BigUint) -> Option<Signature> {
+    let Params { p, q, .. } = &public.params;
+    let r = public.y.modpow(z, p) % q;
+    let s = invmod(z, q)? * &r % q;
+    Some(Signature { r, s })
+}
+
+/// Given the nonce `k` used to produce `sig` over the hash `h`, recover the private key
+/// `x = (s*k - h) / r mod q`.
+pub fn x_from_nonce(params: &Params, h: &BigUint, sig: &Signature, k: &BigUint) -> Option<BigUint> {
+    let q = &params.q;
+    let sk = &sig.s * k % q;
+    let h = h % q;
+    invmod(&sig.r, q).map(|r_inv| (sk + q - h) * r_inv % q)
+}
+
+/// Recover the private key behind `public` by trying every nonce in `0..limit`, checking each
+/// candidate key against `y`.
+pub fn recover_from_weak_nonce(
+    public: &PublicKey,
+    h: &BigUint,
+    sig: &Signature,
+    limit: u32,
+) -> Option<PrivateKey> {
+    let Params { p, q, g } = &public.params;
+
+    // walk g^k incrementally instead of exponentiating from scratch each time
+    let mut g_k = BigUint::one();
+    for k in 0..limit {
+        if &g_k % q == sig.r {
+            if let Some(x) = x_from_nonce(&public.params, h, sig, &BigUint::from(k)) {
+                if g.modpow(&x, p) == public.y {
+                    return Some(PrivateKey {
+                        params: public.params.clone(),
+                        x,
+                    });
+                }
+            }
+        }
+        g_k = g_k * g % p;
+    }
+    None
+}
+
+/// Reasons a list of signed messages could not be parsed.
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseError {
+    /// A record was missing the named field.
+    MissingField(&'static str),
+    /// The named field could not be parsed as a number.
+    BadNumber(&'static str),
+}
+
+/// A message along with its signature, as captured off the wire.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignedMessage {
+    pub msg: String,
+    pub sig: Signature,
+    /// The SHA-1 hash of `msg`.
+    pub m: BigUint,
+}
+
+/// Parse a list of signed messages, each a group of four lines of the form
+///
+/// ```text
+/// msg: <message text>
+/// s: <decimal>
+/// r: <decimal>
+/// m: <hex SHA-1 of the message>
+/// ```
+///
+/// The message text is taken verbatim after `msg: `, trailing whitespace included.
+pub fn parse_signed_messages(input: &str) -> Result<Vec<SignedMessage>, ParseError> {
+    fn field<'a>(line: Option<&'a str>, name: &'static str) -> Result<&'a str, ParseError> {
+        line.and_then(|l| l.strip_prefix(name))
+            .and_then(|l| l.strip_prefix(": "))
+            .ok_or(ParseError::MissingField(name))
+    }
+
+    fn number(value: &str, radix: u32, name: &'static str) -> Result<BigUint, ParseError> {
+        BigUint::from_str_radix(value.trim(), radix).map_err(|_| ParseError::BadNumber(name))
+    }
+
+    let lines = input
+        .lines()
+        .map(|l| l.trim_end_matches('\r'))
+        .filter(|l| !l.trim().is_empty())
+        .collect::<Vec<&str>>();
+
+    lines
+        .chunks(4)
+        .map(|record| {
+            let mut record = record.iter().cloned();
+            let msg = field(record.next(), "msg")?.to_string();
+            let s = number(field(record.next(), "s")?, 10, "s")?;
+            let r = number(field(record.next(), "r")?, 10, "r")?;
+            let m = number(field(record.next(), "m")?, 16, "m")?;
+            Ok(SignedMessage {
+                msg,
+                sig: Signature { r, s },
+                m,
+            })
+        })
+        .collect()
+}
+
+/// Find every pair of messages (by index) whose signatures share an `r`, and thus a nonce.
+pub fn find_repeated_nonces(messages: &[SignedMessage]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, a) in messages.iter().enumerate() {
+        for (j, b) in messages.iter().enumerate().skip(i + 1) {
+            if a.sig.r == b.sig.r {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+/// Recover the nonce shared by two signatures: `k = (m1 - m2) / (s1 - s2) mod q`.
+pub fn recover_repeated_nonce(
+    params: &Params,
+    a: &SignedMessage,
+    b: &SignedMessage,
+) -> Option<BigUint> {
+    let q = &params.q;
+    let dm = (&a.m % q + q - &b.m % q) % q;
+    let ds = (&a.sig.s % q + q - &b.sig.s % q) % q;
+    invmod(&ds, q).map(|ds_inv| dm * ds_inv % q)
+}
+
+/// Recover the private key behind `public` from any pair of `messages` signed with the same
+/// nonce.
+pub fn recover_from_repeated_nonce(
+    public: &PublicKey,
+    messages: &[SignedMessage],
+) -> Option<PrivateKey> {
+    let params = &public.params;
+    find_repeated_nonces(messages)
+        .into_iter()
+        .filter_map(|(i, j)| {
+            let k = recover_repeated_nonce(params, &messages[i], &messages[j])?;
+            x_from_nonce(params, &messages[i].m, &messages[i].sig, &k)
+        })
+        .find(|x| params.g.modpow(x, &params.p) == public.y)
+        .map(|x| PrivateKey {
+            params: params.clone(),
+            x,
+        })
+}
@@ -0,0 +1,194 @@
+/// Duplicate-signature key selection: given somebody else's signature over a message, make up a
+/// key pair of your own under which the same signature verifies.
+///
+/// Signature schemes promise that nobody can forge a signature under somebody else's key. They
+/// don't promise that a signature identifies the key that made it, and protocols which assume
+/// otherwise can be fooled into attributing a message to the wrong signer. Both ECDSA and RSA
+/// fall to this when the attacker gets to pick the rest of their key's parameters.
+use crate::dsa::Signature;
+use crate::ec::{Curve, Point};
+use crate::ecdsa;
+use crate::math::{crt, invmod, is_probable_prime, pohlig_hellman};
+use crate::rsa;
+use crate::rsa::signature::{encode, HashAlgorithm};
+use num_bigint::BigUint;
+use num_traits::One;
+use rand::{thread_rng, Rng};
+
+/// The bound on the prime factors of `p - 1` and `q - 1` for the RSA attack's primes.
+pub const SMOOTHNESS_BOUND: u32 = 1 << 12;
+
+/// Find an ECDSA key pair under which `sig` is a valid signature over `message`, given the
+/// public key it was really made with.
+///
+/// Verification checks that `u1 * G + u2 * Q` has x coordinate `r`. Pick any private key `d'`,
+/// and a new base point `G' = R / (u1 + u2 * d')`, where `R` is that point; then
+/// `u1 * G' + u2 * d' * G' = R` under the new key as well.
+pub fn ecdsa_key(
+    public: &ecdsa::PublicKey,
+    message: &[u8],
+    sig: &Signature,
+) -> Option<ecdsa::PrivateKey> {
+    let curve = &public.curve;
+    let n = &curve.n;
+    let h = ecdsa::hash_message(message, n);
+    let w = invmod(&sig.s, n)?;
+    let u1 = h * &w % n;
+    let u2 = &sig.r * &w % n;
+    let r = curve.add(
+        &curve.multiply(&curve.g, &u1),
+        &curve.multiply(&public.q, &u2),
+    );
+    if r == Point::Infinity {
+        return None;
+    }
+
+    loop {
+        let d = curve.generate_private();
+        if let Some(t_inv) = invmod(&((&u1 + &u2 * &d) % n), n) {
+            return Some(ecdsa::PrivateKey {
+                curve: Curve {
+                    g: curve.multiply(&r, &t_inv),
+                    ..curve.clone()
+                },
+                d,
+            });
+        }
+    }
+}
+
+/// The primes below `bound`, by the sieve of Eratosthenes.
+fn primes_below(bound: u32) -> Vec<u32> {
+    let mut composite = vec![false; bound as usize];
+    let mut primes = Vec::new();
+    for i in 2..bound as usize {
+        if !composite[i] {
+            primes.push(i as u32);
+            for j in (i * i..bound as usize).step_by(i) {
+                composite[j] = true;
+            }
+        }
+    }
+    primes
+}
+
+/// Generate a prime `p` of exactly `bits` bits for which `p - 1` is 2 times a product of
+/// distinct odd primes from `primes`, none of them in `avoid`. Returns `p` and the prime factors
+/// of `p - 1`.
+fn smooth_prime(bits: usize, primes: &[u32], avoid: &[u32]) -> (BigUint, Vec<u32>) {
+    let mut rng = thread_rng();
+    let usable = primes
+        .iter()
+        .cloned()
+        .filter(|r| *r != 2 && !avoid.contains(r))
+        .collect::<Vec<_>>();
+    let top = BigUint::one() << bits;
+    let half = BigUint::one() << (bits - 1);
+
+    loop {
+        let mut factors = vec![2];
+        let mut product = BigUint::from(2u32);
+        // leave room for one last factor, of up to log2(SMOOTHNESS_BOUND) bits, which brings it
+        // to exactly the right size
+        while product.bits() + (SMOOTHNESS_BOUND.trailing_zeros() as usize) < bits {
+            let r = usable[rng.gen_range(0, usable.len())];
+            if !factors.contains(&r) {
+                factors.push(r);
+                product *= r;
+            }
+        }
+
+        let low = (&half + &product - 1u32) / &product;
+        let high = (&top - 1u32) / &product;
+        let last = usable
+            .iter()
+            .cloned()
+            .filter(|r| !factors.contains(r))
+            .filter(|&r| BigUint::from(r) >= low && BigUint::from(r) <= high)
+            .collect::<Vec<_>>();
+        if last.is_empty() {
+            continue;
+        }
+        let r = last[rng.gen_range(0, last.len())];
+        factors.push(r);
+        let p = product * r + 1u32;
+        if is_probable_prime(&p, 32) {
+            return (p, factors);
+        }
+    }
+}
+
+/// Generate a smooth prime as in `smooth_prime` for which `s` is a primitive root, so that
+/// every nonzero value is a power of it. Returns the prime, the factors of `p - 1`, and the
+/// discrete log of `target` to the base `s`.
+fn smooth_prime_for(
+    bits: usize,
+    primes: &[u32],
+    avoid: &[u32],
+    s: &BigUint,
+    target: &BigUint,
+) -> (BigUint, Vec<u32>, BigUint) {
+    loop {
+        let (p, factors) = smooth_prime(bits, primes, avoid);
+        let order = &p - 1u32;
+        let primitive = factors
+            .iter()
+            .all(|&r| !s.modpow(&(&order / r), &p).is_one());
+        if !primitive {
+            continue;
+        }
+        if let Some(e) = pohlig_hellman(s, target, &p, &factors) {
+            return (p, factors, e);
+        }
+    }
+}
+
+/// Find an RSA key pair under which `signature` is a valid PKCS#1 v1.5 signature over `message`,
+/// given the public key it was really made with, whose modulus fixes the key size.
+///
+/// With the encoded message block `pad` and signature `s`, we want `s^e' = pad mod N'`. Choose
+/// primes `p` and `q` for which `p - 1` and `q - 1` are smooth and `s` is a primitive root, and
+/// the discrete logs of `pad` mod each fall out of Pohlig-Hellman. Combined with the CRT they
+/// give `e'`, and since we know the factors of `N'` we can find the matching `d'`.
+///
+/// `e'` also has to be invertible mod `(p - 1) * (q - 1)`, so each prime is regenerated until
+/// the log mod that prime is invertible mod `p - 1`; `p` must also leave room for a `q` of the
+/// right size which makes `N'` bigger than `s`. Both logs are then odd, which is just as
+/// well, since 2 is the one factor `p - 1` and `q - 1` share and they must agree mod it.
+pub fn rsa_key(
+    public: &rsa::PublicKey,
+    alg: HashAlgorithm,
+    message: &[u8],
+    signature: &[u8],
+) -> Option<(rsa::PublicKey, rsa::PrivateKey)> {
+    let size = public.size();
+    let pad = BigUint::from_bytes_be(&encode(alg, message, size).ok()?);
+    let s = BigUint::from_bytes_be(signature);
+    let primes = primes_below(SMOOTHNESS_BOUND);
+    let p_bits = public.n.bits() / 2;
+    let q_bits = public.n.bits() - p_bits;
+
+    let invertible = |e: &BigUint, p: &BigUint| invmod(e, &(p - 1u32)).is_some();
+
+    let (p, p_factors, e_p) = loop {
+        let (p, factors, e) = smooth_prime_for(p_bits, &primes, &[], &s, &pad);
+        // unless some q of the right size takes N' past s, the search for q below never ends
+        let biggest = &p << q_bits;
+        if invertible(&e, &p) && biggest > s && biggest > pad {
+            break (p, factors, e);
+        }
+    };
+    let (q, e_q) = loop {
+        let (q, _, e) = smooth_prime_for(q_bits, &primes, &p_factors, &s, &pad);
+        let n = &p * &q;
+        if n > s && n > pad && n.bits().div_ceil(8) == size && invertible(&e, &q) {
+            break (q, e);
+        }
+    };
+
+    let half_q_order = (&q - 1u32) / 2u32;
+    let (e, _) = crt(&[(e_p, &p - 1u32), (e_q % &half_q_order, half_q_order)])?;
+    let d = invmod(&e, &((&p - 1u32) * (&q - 1u32)))?;
+    let n = p * q;
+    Some((rsa::PublicKey { e, n: n.clone() }, rsa::PrivateKey { d, n }))
+}
@@ -0,0 +1,209 @@
+/// Elliptic curves in short Weierstrass form, `y^2 = x^3 + a*x + b` over the integers mod a
+/// prime, with points in affine coordinates, and Diffie-Hellman over them.
+use crate::dh::DhGroup;
+use crate::kangaroo::AbelianGroup;
+use crate::math::{invmod, rand_below, rand_range, sqrt_mod};
+use num_bigint::BigUint;
+use num_traits::{Num, One, Zero};
+
+pub mod invalid_curve;
+pub mod montgomery;
+pub mod twist;
+
+/// A point on a curve: either the point at infinity, which is the identity, or a pair of
+/// coordinates.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Point {
+    Infinity,
+    Affine(BigUint, BigUint),
+}
+
+/// A curve `y^2 = x^3 + a*x + b` mod `p`, with a base point `g` of order `n`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Curve {
+    pub p: BigUint,
+    pub a: BigUint,
+    pub b: BigUint,
+    pub g: Point,
+    pub n: BigUint,
+}
+
+impl Curve {
+    /// The curve from challenge 59, `y^2 = x^3 - 95051*x + 11279326`, whose base point has a
+    /// prime order about an eighth the size of `p`.
+    pub fn challenge59() -> Self {
+        let p = BigUint::from_str_radix("233970423115425145524320034830162017933", 10).unwrap();
+        Curve {
+            a: &p - 95051u32,
+            b: BigUint::from(11_279_326u32),
+            g: Point::Affine(
+                BigUint::from(182u32),
+                BigUint::from_str_radix("85518893674295321206118380980485522083", 10).unwrap(),
+            ),
+            n: BigUint::from_str_radix("29246302889428143187362802287225875743", 10).unwrap(),
+            p,
+        }
+    }
+
+    /// The same curve with a different `b`. The addition formulas never look at `b`, so code
+    /// which doesn't check its inputs will happily do arithmetic on this curve instead.
+    ///
+    /// The base point isn't on the new curve, so it's replaced with the point at infinity, and
+    /// `n` with the order of the whole group, which must be supplied.
+    pub fn with_b(&self, b: BigUint, order: BigUint) -> Self {
+        Curve {
+            p: self.p.clone(),
+            a: self.a.clone(),
+            b,
+            g: Point::Infinity,
+            n: order,
+        }
+    }
+
+    /// `x^3 + a*x + b`, the square `y` has to be for `(x, y)` to be on the curve.
+    fn rhs(&self, x: &BigUint) -> BigUint {
+        (x * x % &self.p * x + &self.a * x + &self.b) % &self.p
+    }
+
+    /// Check that `point` is on this curve.
+    pub fn contains(&self, point: &Point) -> bool {
+        match point {
+            Point::Infinity => true,
+            Point::Affine(x, y) => x < &self.p && y < &self.p && y * y % &self.p == self.rhs(x),
+        }
+    }
+
+    /// Pick a random point on the curve, other than the point at infinity.
+    pub fn random_point(&self) -> Point {
+        loop {
+            let x = rand_below(&self.p);
+            if let Some(y) = sqrt_mod(&self.rhs(&x), &self.p) {
+                return Point::Affine(x, y);
+            }
+        }
+    }
+
+    /// The inverse of `point`, its reflection in the x-axis.
+    pub fn negate(&self, point: &Point) -> Point {
+        match point {
+            Point::Infinity => Point::Infinity,
+            Point::Affine(x, y) => Point::Affine(x.clone(), (&self.p - y) % &self.p),
+        }
+    }
+
+    /// Add two points.
+    pub fn add(&self, p1: &Point, p2: &Point) -> Point {
+        let (x1, y1, x2, y2) = match (p1, p2) {
+            (Point::Infinity, _) => return p2.clone(),
+            (_, Point::Infinity) => return p1.clone(),
+            (Point::Affine(x1, y1), Point::Affine(x2, y2)) => (x1, y1, x2, y2),
+        };
+        if *p1 == self.negate(p2) {
+            return Point::Infinity;
+        }
+
+        let p = &self.p;
+        let m = if p1 == p2 {
+            (x1 * x1 * 3u32 + &self.a) * invmod(&(y1 * 2u32 % p), p).expect("y is nonzero") % p
+        } else {
+            (y2 + p - y1) * invmod(&((x2 + p - x1) % p), p).expect("x1 != x2") % p
+        };
+        let x3 = (&m * &m + p * 2u32 - x1 - x2) % p;
+        let y3 = (m * ((x1 + p - &x3) % p) + p - y1) % p;
+        Point::Affine(x3, y3)
+    }
+
+    /// Add a point to itself.
+    pub fn double(&self, point: &Point) -> Point {
+        self.add(point, point)
+    }
+
+    /// Multiply `point` by `k`, by double-and-add.
+    pub fn multiply(&self, point: &Point, k: &BigUint) -> Point {
+        let mut result = Point::Infinity;
+        for i in (0..k.bits()).rev() {
+            result = self.double(&result);
+            if !((k >> i) & BigUint::one()).is_zero() {
+                result = self.add(&result, point);
+            }
+        }
+        result
+    }
+
+    /// Generate a random private key in `[1, n)`.
+    pub fn generate_private(&self) -> BigUint {
+        rand_range(&BigUint::one(), &self.n)
+    }
+
+    /// The public key `x * g` for private key `x`.
+    pub fn public(&self, x: &BigUint) -> Point {
+        self.multiply(&self.g, x)
+    }
+
+    /// The shared secret `x * their_public`. Nothing checks that `their_public` is on the curve,
+    /// which is exactly the mistake `invalid_curve` takes advantage of.
+    pub fn shared_secret(&self, x: &BigUint, their_public: &Point) -> Point {
+        self.multiply(their_public, x)
+    }
+
+    /// The shared secret `x * their_public`, or `None` if `their_public` isn't on the curve.
+    pub fn checked_shared_secret(&self, x: &BigUint, their_public: &Point) -> Option<Point> {
+        if self.contains(their_public) {
+            Some(self.shared_secret(x, their_public))
+        } else {
+            None
+        }
+    }
+}
+
+/// Point addition, written multiplicatively for the sake of the trait.
+impl AbelianGroup for Curve {
+    type Element = Point;
+
+    fn identity(&self) -> Point {
+        Point::Infinity
+    }
+
+    fn combine(&self, a: &Point, b: &Point) -> Point {
+        self.add(a, b)
+    }
+
+    fn scale(&self, a: &Point, k: &BigUint) -> Point {
+        self.multiply(a, k)
+    }
+
+    fn fingerprint(&self, a: &Point) -> u64 {
+        let x = match a {
+            Point::Infinity => return 0,
+            Point::Affine(x, _) => x,
+        };
+        let mut low = [0u8; 8];
+        for (l, b) in low.iter_mut().zip(x.to_bytes_le()) {
+            *l = b;
+        }
+        u64::from_le_bytes(low)
+    }
+}
+
+/// The key for MACs under a shared secret point is its coordinates, concatenated, or nothing for
+/// the point at infinity.
+impl DhGroup for Curve {
+    fn generator(&self) -> &Point {
+        &self.g
+    }
+
+    fn order(&self) -> &BigUint {
+        &self.n
+    }
+
+    fn mac_key(&self, k: &Point) -> Vec<u8> {
+        match k {
+            Point::Infinity => Vec::new(),
+            Point::Affine(x, y) => {
+                let mut key = x.to_bytes_be();
+                key.extend(y.to_bytes_be());
+                key
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+/// The invalid-curve attack on elliptic-curve Diffie-Hellman.
+///
+/// The point addition formulas never use the curve's `b`, so a party which doesn't check that
+/// the other side's public key is on the curve will compute with a point on some other curve
+/// `y^2 = x^3 + a*x + b'`. If that curve's group has small subgroups, a point of small order `r`
+/// confines the shared secret to `r` possible values, and anything authenticated with it gives
+/// away the private key modulo `r`. Points from a few such curves give enough residues to pin
+/// down the whole key by the Chinese remainder theorem.
+///
+/// That's the small-subgroup attack again, so Bob and the key recovery are those of
+/// `dh::small_subgroup`; all this module adds is the points of small order.
+use super::{Curve, Point};
+use crate::dh::small_subgroup::FACTOR_BOUND;
+use crate::math::small_factors;
+use num_bigint::BigUint;
+use num_traits::{Num, Zero};
+
+/// The bogus curves from challenge 59, sharing `a` and `p` with `Curve::challenge59` but with
+/// `b` of 210, 504 and 727, each with its group order.
+pub fn challenge59_bogus_curves(curve: &Curve) -> Vec<Curve> {
+    [
+        (210u32, "233970423115425145550826547352470124412"),
+        (504, "233970423115425145544350131142039591210"),
+        (727, "233970423115425145545378039958152057148"),
+    ]
+    .iter()
+    .map(|&(b, order)| {
+        curve.with_b(
+            BigUint::from(b),
+            BigUint::from_str_radix(order, 10).unwrap(),
+        )
+    })
+    .collect()
+}
+
+/// Find a random point of order `r` on `bogus`, where `r` is a prime dividing its order `n`.
+///
+/// The group needn't be cyclic, so multiplying by `n / r` can wipe out every point. Instead this
+/// strips all the factors of `r` from `n` to land in the `r`-part of the group, then multiplies
+/// by `r` until one more step would reach the point at infinity.
+pub fn point_of_order(bogus: &Curve, r: u32) -> Point {
+    let r = BigUint::from(r);
+    let mut cofactor = bogus.n.clone();
+    while (&cofactor % &r).is_zero() {
+        cofactor /= &r;
+    }
+    loop {
+        let mut h = bogus.multiply(&bogus.random_point(), &cofactor);
+        if h == Point::Infinity {
+            continue;
+        }
+        loop {
+            let next = bogus.multiply(&h, &r);
+            if next == Point::Infinity {
+                return h;
+            }
+            h = next;
+        }
+    }
+}
+
+/// A point of order `r` for each small prime factor `r` of each of the `bogus_curves`' orders,
+/// found as needed.
+///
+/// None of these points are on the real curve, but the addition formulas never use `b`, so
+/// `small_subgroup` can do arithmetic with them on the real curve and get the same results Bob
+/// does.
+pub fn small_order_points(bogus_curves: &[Curve]) -> impl Iterator<Item = (u32, Point)> + '_ {
+    bogus_curves.iter().flat_map(|bogus| {
+        small_factors(&bogus.n, FACTOR_BOUND)
+            .into_iter()
+            .map(move |r| (r, point_of_order(bogus, r)))
+    })
+}
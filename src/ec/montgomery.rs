@@ -0,0 +1,238 @@
+/// Curves in Montgomery form, `B*v^2 = u^3 + A*u^2 + u`, with x-only arithmetic.
+///
+/// The Montgomery ladder computes the `u` coordinate of `k * P` from nothing but the `u`
+/// coordinate of `P`, in constant time. Every `u` is the coordinate of a point either on the
+/// curve or on its quadratic twist, and the ladder works just the same for both, which is what
+/// `twist` takes advantage of.
+use super::{Curve, Point};
+use crate::math::{invmod, rand_range, sqrt_mod};
+use num_bigint::BigUint;
+use num_traits::{Num, One, Zero};
+
+/// A curve `B*v^2 = u^3 + A*u^2 + u` mod `p`, with a base point `g` of order `n`, and
+/// `cofactor * n` points in all.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MontgomeryCurve {
+    pub p: BigUint,
+    pub a: BigUint,
+    pub b: BigUint,
+    pub g: Point,
+    pub n: BigUint,
+    pub cofactor: u32,
+}
+
+impl MontgomeryCurve {
+    /// The curve from challenge 60, `v^2 = u^3 + 534*u^2 + u`, which is the curve from challenge
+    /// 59 in disguise.
+    pub fn challenge60() -> Self {
+        MontgomeryCurve {
+            p: BigUint::from_str_radix("233970423115425145524320034830162017933", 10).unwrap(),
+            a: BigUint::from(534u32),
+            b: BigUint::one(),
+            g: Point::Affine(
+                BigUint::from(4u32),
+                BigUint::from_str_radix("85518893674295321206118380980485522083", 10).unwrap(),
+            ),
+            n: BigUint::from_str_radix("29246302889428143187362802287225875743", 10).unwrap(),
+            cofactor: 8,
+        }
+    }
+
+    /// `(a - b) mod p`.
+    fn sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a + &self.p - b % &self.p) % &self.p
+    }
+
+    /// `a / b mod p`.
+    fn div(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * invmod(b, &self.p).expect("p is prime") % &self.p
+    }
+
+    /// `(u^3 + A*u^2 + u) / B`, the square `v` has to be for `(u, v)` to be on the curve.
+    fn rhs(&self, u: &BigUint) -> BigUint {
+        let u2 = u * u % &self.p;
+        self.div(&((&u2 * u + &self.a * &u2 + u) % &self.p), &self.b)
+    }
+
+    /// The `u` coordinate of the base point.
+    pub fn base_u(&self) -> BigUint {
+        match &self.g {
+            Point::Infinity => BigUint::zero(),
+            Point::Affine(u, _) => u.clone(),
+        }
+    }
+
+    /// Check that `u` is the coordinate of a point on this curve, rather than on its twist.
+    pub fn contains_u(&self, u: &BigUint) -> bool {
+        let rhs = self.rhs(u);
+        rhs.is_zero() || rhs.modpow(&((&self.p - 1u32) >> 1), &self.p).is_one()
+    }
+
+    /// Find a point with coordinate `u`, if there is one on this curve. The other has `-v`.
+    pub fn lift(&self, u: &BigUint) -> Option<Point> {
+        sqrt_mod(&self.rhs(u), &self.p).map(|v| Point::Affine(u % &self.p, v))
+    }
+
+    /// The number of points on the quadratic twist, `2*p + 2` minus the number on the curve.
+    pub fn twist_order(&self) -> BigUint {
+        &self.p * 2u32 + 2u32 - &self.n * self.cofactor
+    }
+
+    /// Pick a random `u` which is on the twist rather than the curve.
+    pub fn random_twist_u(&self) -> BigUint {
+        loop {
+            let u = rand_range(&BigUint::one(), &self.p);
+            if !self.contains_u(&u) {
+                return u;
+            }
+        }
+    }
+
+    /// The `u` coordinate of `k` times the point with coordinate `u`, by the Montgomery ladder.
+    /// The point at infinity comes out as zero. It always takes at least as many steps as `p` has
+    /// bits, however small `k` is, and more if `k` is bigger.
+    pub fn ladder(&self, u: &BigUint, k: &BigUint) -> BigUint {
+        let p = &self.p;
+        let (mut u2, mut w2) = (BigUint::one(), BigUint::zero());
+        let (mut u3, mut w3) = (u % p, BigUint::one());
+        for i in (0..k.bits().max(p.bits())).rev() {
+            let bit = !((k >> i) & BigUint::one()).is_zero();
+            if bit {
+                std::mem::swap(&mut u2, &mut u3);
+                std::mem::swap(&mut w2, &mut w3);
+            }
+            let sum = self.sub(&(&u2 * &u3), &(&w2 * &w3));
+            let diff = self.sub(&(&u2 * &w3), &(&w2 * &u3));
+            u3 = &sum * &sum % p;
+            w3 = u * (&diff * &diff % p) % p;
+            let squares = self.sub(&(&u2 * &u2), &(&w2 * &w2));
+            let (next_u2, next_w2) = (
+                &squares * &squares % p,
+                (&u2 * &w2 * 4u32 % p) * ((&u2 * &u2 + &self.a * &u2 * &w2 + &w2 * &w2) % p) % p,
+            );
+            u2 = next_u2;
+            w2 = next_w2;
+            if bit {
+                std::mem::swap(&mut u2, &mut u3);
+                std::mem::swap(&mut w2, &mut w3);
+            }
+        }
+        if w2.is_zero() {
+            BigUint::zero()
+        } else {
+            self.div(&u2, &w2)
+        }
+    }
+
+    /// The `u` coordinates of `0 * P, 1 * P, 2 * P, ...` where `P` has coordinate `u`, each from
+    /// the last two by differential addition. Much cheaper than a ladder for each.
+    pub fn multiples(&self, u: &BigUint) -> Multiples<'_> {
+        Multiples {
+            curve: self,
+            u: u % &self.p,
+            previous: None,
+            current: (BigUint::one(), BigUint::zero()),
+        }
+    }
+
+    /// Generate a random private key in `[1, n)`.
+    pub fn generate_private(&self) -> BigUint {
+        rand_range(&BigUint::one(), &self.n)
+    }
+
+    /// The public key, the `u` coordinate of `x * g`, for private key `x`.
+    pub fn public(&self, x: &BigUint) -> BigUint {
+        self.ladder(&self.base_u(), x)
+    }
+
+    /// The shared secret `u` coordinate of `x * their_public`. Nothing checks that
+    /// `their_public` is on the curve rather than the twist.
+    pub fn shared_secret(&self, x: &BigUint, their_public: &BigUint) -> BigUint {
+        self.ladder(their_public, x)
+    }
+
+    /// The same curve in short Weierstrass form, by `x = u/B + A/3B` and `y = v/B`.
+    pub fn to_weierstrass(&self) -> Curve {
+        let p = &self.p;
+        let (a, b) = (&self.a, &self.b);
+        let b2 = b * b % p;
+        let a2 = a * a % p;
+        Curve {
+            p: p.clone(),
+            a: self.div(&self.sub(&BigUint::from(3u32), &a2), &(&b2 * 3u32)),
+            b: self.div(&self.sub(&(&a2 * a * 2u32), &(a * 9u32)), &(b2 * b * 27u32)),
+            g: self.to_weierstrass_point(&self.g),
+            n: self.n.clone(),
+        }
+    }
+
+    /// Map a point on this curve to the same point on `to_weierstrass()`.
+    pub fn to_weierstrass_point(&self, point: &Point) -> Point {
+        match point {
+            Point::Infinity => Point::Infinity,
+            Point::Affine(u, v) => {
+                let three_b = &self.b * 3u32;
+                let x = (self.div(u, &self.b) + self.div(&self.a, &three_b)) % &self.p;
+                Point::Affine(x, self.div(v, &self.b))
+            }
+        }
+    }
+
+    /// Map a point on `to_weierstrass()` back to this curve.
+    pub fn from_weierstrass_point(&self, point: &Point) -> Point {
+        match point {
+            Point::Infinity => Point::Infinity,
+            Point::Affine(x, y) => {
+                let u = self.sub(&(&self.b * x), &self.div(&self.a, &BigUint::from(3u32)));
+                Point::Affine(u, &self.b * y % &self.p)
+            }
+        }
+    }
+}
+
+/// An iterator over the `u` coordinates of successive multiples of a point, from
+/// `MontgomeryCurve::multiples`.
+pub struct Multiples<'a> {
+    curve: &'a MontgomeryCurve,
+    u: BigUint,
+    /// The last multiple, projectively, or `None` before the first.
+    previous: Option<(BigUint, BigUint)>,
+    current: (BigUint, BigUint),
+}
+
+impl<'a> Iterator for Multiples<'a> {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<BigUint> {
+        let curve = self.curve;
+        let p = &curve.p;
+        let (x, z) = &self.current;
+        let item = if z.is_zero() {
+            BigUint::zero()
+        } else {
+            curve.div(x, z)
+        };
+
+        let next = match &self.previous {
+            // 0 * P is the point at infinity, and 1 * P is (u : 1)
+            None => (self.u.clone(), BigUint::one()),
+            // differential addition needs a difference other than the point at infinity, but if
+            // the last multiple was the point at infinity this one is P, so double it
+            Some((_, z0)) if z0.is_zero() => {
+                let squares = curve.sub(&(x * x), &(z * z));
+                (
+                    &squares * &squares % p,
+                    (x * z * 4u32 % p) * ((x * x + &curve.a * x * z + z * z) % p) % p,
+                )
+            }
+            // (k+1) * P = k * P + P, with difference (k-1) * P
+            Some((x0, z0)) => {
+                let sum = curve.sub(&(x * &self.u), z);
+                let diff = curve.sub(x, &(z * &self.u));
+                (z0 * (&sum * &sum % p) % p, x0 * (&diff * &diff % p) % p)
+            }
+        };
+        self.previous = Some(std::mem::replace(&mut self.current, next));
+        Some(item)
+    }
+}
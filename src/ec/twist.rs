@@ -0,0 +1,194 @@
+/// The single-coordinate twist attack on x-only Montgomery ECDH.
+///
+/// A party using the Montgomery ladder only ever sees the `u` coordinate of the other side's
+/// public key, so there's no bogus `b` to send. But every `u` which isn't on the curve is on its
+/// quadratic twist, and the ladder computes on the twist just as happily. If the twist's order
+/// has small factors, points of small order on it leak the private key modulo each factor, up
+/// to sign, since `k * P` and `-k * P` share a `u` coordinate. The residues fix the key modulo
+/// their product, and the kangaroo finds the rest.
+use super::montgomery::MontgomeryCurve;
+use crate::dh::small_subgroup::{authenticate, match_residue, Residue};
+use crate::hmac::hmac_sha256;
+use crate::kangaroo::catch_with_residues;
+use crate::math::{crt, small_factors};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// The largest prime factor of the twist's order worth brute-forcing a residue for. Higher than
+/// `small_subgroup::FACTOR_BOUND`, since the residues have to leave the kangaroo little enough
+/// to search.
+pub const TWIST_FACTOR_BOUND: u32 = 1 << 22;
+
+/// The x-only counterpart of `small_subgroup::Bob`: given any public `u`, it derives
+/// `K = u(x * P)` with the ladder and sends back a message with its MAC under `K`. Unless told to
+/// check, it doesn't care whether `u` is on the curve or on the twist.
+pub struct Bob {
+    curve: MontgomeryCurve,
+    x: BigUint,
+    check_on_curve: bool,
+}
+
+impl Bob {
+    /// Create Bob with private key `x` on `curve`, refusing public keys on the twist if
+    /// `check_on_curve` is set.
+    pub fn new(curve: MontgomeryCurve, x: BigUint, check_on_curve: bool) -> Self {
+        Bob {
+            curve,
+            x,
+            check_on_curve,
+        }
+    }
+
+    /// Bob's public key.
+    pub fn public_key(&self) -> BigUint {
+        self.curve.public(&self.x)
+    }
+
+    /// Respond to public key `u` with `(message, MAC)`, or `None` if Bob is checking and `u`
+    /// isn't on the curve.
+    pub fn respond(&self, u: &BigUint) -> Option<(Vec<u8>, Vec<u8>)> {
+        if self.check_on_curve && !self.curve.contains_u(u) {
+            return None;
+        }
+        let k = self.curve.shared_secret(&self.x, u);
+        Some(authenticate(&k.to_bytes_be()))
+    }
+}
+
+/// Find a random `u` on the twist of order exactly `order`, a product of the distinct primes
+/// `factors`, none of which divides the twist's order more than once.
+pub fn twist_point_of_order(curve: &MontgomeryCurve, order: &BigUint, factors: &[u32]) -> BigUint {
+    let cofactor = curve.twist_order() / order;
+    loop {
+        let u = curve.ladder(&curve.random_twist_u(), &cofactor);
+        if factors
+            .iter()
+            .all(|&r| !curve.ladder(&u, &(order / r)).is_zero())
+        {
+            return u;
+        }
+    }
+}
+
+/// Recover the private key behind `oracle` modulo the prime `r`, up to sign, by sending it a
+/// point of order `r` on the twist and trying each of the possible shared secrets against the
+/// MAC it returns. The residue's value is the smaller of the two candidates. Returns `None` if
+/// the oracle refuses the point.
+pub fn recover_residue<O>(curve: &MontgomeryCurve, r: u32, oracle: &mut O) -> Option<Residue>
+where
+    O: FnMut(&BigUint) -> Option<(Vec<u8>, Vec<u8>)>,
+{
+    let h = twist_point_of_order(curve, &BigUint::from(r), &[r]);
+    let (message, mac) = oracle(&h)?;
+
+    let keys = curve.multiples(&h).take(r as usize / 2 + 1);
+    match_residue(r, keys.map(|k| k.to_bytes_be()), &message, &mac)
+}
+
+/// Recover residues of the private key behind `oracle`, up to sign, for the small odd prime
+/// factors of the twist's order, passing each to `log` as it's found. Returns `None` if the
+/// oracle refuses a point.
+pub fn recover_residues<O, L>(
+    curve: &MontgomeryCurve,
+    oracle: &mut O,
+    mut log: L,
+) -> Option<Vec<Residue>>
+where
+    O: FnMut(&BigUint) -> Option<(Vec<u8>, Vec<u8>)>,
+    L: FnMut(&Residue),
+{
+    let order = curve.twist_order();
+    let mut residues = Vec::new();
+    for r in small_factors(&order, TWIST_FACTOR_BOUND) {
+        // the sign ambiguity swallows everything a factor of two would tell us, and a repeated
+        // factor would need points of higher order to resolve
+        if r == 2 || (&order % (r as u64 * r as u64)).is_zero() {
+            continue;
+        }
+        let residue = recover_residue(curve, r, oracle)?;
+        log(&residue);
+        residues.push(residue);
+    }
+    Some(residues)
+}
+
+/// Combine residues known only up to sign into `(n, M)` with the key `x = ±n mod M`.
+///
+/// With `x = ±n mod M` so far and `x = ±k mod r` from the next residue, there are two different
+/// candidates for `x` mod `M * r`, up to sign. A point of order `M * r` sent to `oracle` tells
+/// them apart. Returns `None` if the oracle refuses a point.
+pub fn resolve_signs<O>(
+    curve: &MontgomeryCurve,
+    residues: &[Residue],
+    oracle: &mut O,
+) -> Option<(BigUint, BigUint)>
+where
+    O: FnMut(&BigUint) -> Option<(Vec<u8>, Vec<u8>)>,
+{
+    let mut n = BigUint::zero();
+    let mut m = BigUint::one();
+    let mut factors = Vec::new();
+    for residue in residues {
+        let r = BigUint::from(residue.modulus);
+        let k = BigUint::from(residue.value);
+        factors.push(residue.modulus);
+        if m.is_one() {
+            // the first residue's sign is as good as any
+            n = k;
+            m = r;
+            continue;
+        }
+
+        let candidates = [&k, &((&r - &k) % &r)]
+            .iter()
+            .map(|&k| crt(&[(n.clone(), m.clone()), (k.clone(), r.clone())]))
+            .collect::<Option<Vec<_>>>()?;
+        m *= &r;
+
+        let h = twist_point_of_order(curve, &m, &factors);
+        let (message, mac) = oracle(&h)?;
+        n = candidates.into_iter().map(|(c, _)| c).find(|c| {
+            let secret = curve.ladder(&h, c);
+            hmac_sha256(&secret.to_bytes_be(), &message) == mac
+        })?;
+    }
+    Some((n, m))
+}
+
+/// Recover the private key behind `oracle`, whose public key is `public`, up to sign: the result
+/// is either `x` or `n - x`, which are the same key as far as the ladder is concerned. The
+/// residues from the twist fix it modulo their product, and the kangaroo finds the rest on the
+/// curve in Weierstrass form. Returns `None` if the oracle refuses a point.
+pub fn recover_key<O, L>(
+    curve: &MontgomeryCurve,
+    public: &BigUint,
+    mut oracle: O,
+    log: L,
+) -> Option<BigUint>
+where
+    O: FnMut(&BigUint) -> Option<(Vec<u8>, Vec<u8>)>,
+    L: FnMut(&Residue),
+{
+    let residues = recover_residues(curve, &mut oracle, log)?;
+    let (n, m) = resolve_signs(curve, &residues, &mut oracle)?;
+
+    // the lift of the public key is either x * g or (order - x) * g, and x = ±n mod m, but m
+    // divides the twist's order rather than the curve's, so the second has residues of its own
+    let weierstrass = curve.to_weierstrass();
+    let y = curve.to_weierstrass_point(&curve.lift(public)?);
+    let order = &weierstrass.n % &m;
+    let residues = [
+        n.clone(),
+        (&m - &n) % &m,
+        (&order + &m - &n) % &m,
+        (&order + &n) % &m,
+    ];
+    catch_with_residues(
+        &weierstrass,
+        &weierstrass.g,
+        &y,
+        &residues,
+        &m,
+        &weierstrass.n,
+    )
+}
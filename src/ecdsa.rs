@@ -0,0 +1,112 @@
+/// The Elliptic Curve Digital Signature Algorithm, over the curves in `ec`.
+///
+/// Signatures have the same shape as DSA's, so they reuse `dsa::Signature`.
+use crate::dsa::Signature;
+use crate::ec::{Curve, Point};
+use crate::math::{invmod, rand_range};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use sha1::{Digest, Sha1};
+
+pub mod biased_nonce;
+
+/// An ECDSA public key: the point `q = d * g`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PublicKey {
+    pub curve: Curve,
+    pub q: Point,
+}
+
+/// An ECDSA private key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrivateKey {
+    pub curve: Curve,
+    pub d: BigUint,
+}
+
+/// Hash `message` with SHA-1 and keep as many of the leftmost bits as `n` has.
+pub fn hash_message(message: &[u8], n: &BigUint) -> BigUint {
+    let digest = Sha1::digest(message);
+    let h = BigUint::from_bytes_be(&digest);
+    let excess = (digest.len() * 8).saturating_sub(n.bits());
+    h >> excess
+}
+
+/// The x coordinate of `point` mod `n`, or zero for the point at infinity.
+fn x_mod(point: &Point, n: &BigUint) -> BigUint {
+    match point {
+        Point::Infinity => BigUint::zero(),
+        Point::Affine(x, _) => x % n,
+    }
+}
+
+/// Generate a random keypair on `curve`.
+pub fn generate_keypair(curve: &Curve) -> (PublicKey, PrivateKey) {
+    let private = PrivateKey {
+        curve: curve.clone(),
+        d: curve.generate_private(),
+    };
+    (private.public_key(), private)
+}
+
+impl PrivateKey {
+    /// Derive the public key `q = d * g`.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey {
+            curve: self.curve.clone(),
+            q: self.curve.public(&self.d),
+        }
+    }
+
+    /// Sign `message` with a fresh random nonce.
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        let h = hash_message(message, &self.curve.n);
+        loop {
+            let k = rand_range(&BigUint::one(), &self.curve.n);
+            if let Some(sig) = self.sign_with_nonce(&h, &k) {
+                return sig;
+            }
+        }
+    }
+
+    /// Sign the message hash `h` using the nonce `k`. Returns `None` if the nonce produces a
+    /// signature with a zero component, in which case a different nonce must be chosen.
+    pub fn sign_with_nonce(&self, h: &BigUint, k: &BigUint) -> Option<Signature> {
+        let n = &self.curve.n;
+        let r = x_mod(&self.curve.public(k), n);
+        let s = invmod(k, n)? * (h + &self.d * &r) % n;
+        if r.is_zero() || s.is_zero() {
+            None
+        } else {
+            Some(Signature { r, s })
+        }
+    }
+}
+
+impl PublicKey {
+    /// Verify that `sig` is a valid signature over `message`.
+    pub fn verify(&self, message: &[u8], sig: &Signature) -> bool {
+        self.verify_hash(&hash_message(message, &self.curve.n), sig)
+    }
+
+    /// Verify that `sig` is a valid signature over the message hash `h`.
+    pub fn verify_hash(&self, h: &BigUint, sig: &Signature) -> bool {
+        let n = &self.curve.n;
+        if sig.r.is_zero() || &sig.r >= n || sig.s.is_zero() || &sig.s >= n {
+            return false;
+        }
+
+        let w = match invmod(&sig.s, n) {
+            Some(w) => w,
+            None => return false,
+        };
+        let u1 = h * &w % n;
+        let u2 = &sig.r * &w % n;
+        let curve = &self.curve;
+        let point = curve.add(
+            &curve.multiply(&curve.g, &u1),
+            &curve.multiply(&self.q, &u2),
+        );
+        point != Point::Infinity && x_mod(&point, n) == sig.r
+    }
+}
@@ -0,0 +1,131 @@
+/// Key recovery from ECDSA signatures whose nonces are biased, as a hidden number problem.
+///
+/// If every nonce has its low `l` bits zero, then `k = 2^l * b` with `b < q / 2^l`, and
+/// rearranging the signing equation gives `d * t - u = b mod q` for each signature, with `t` and
+/// `u` known. That's a set of linear equations with unusually small solutions, which is just the
+/// kind of thing a lattice can find: a short vector in the lattice below has the `b`s as most of
+/// its coordinates and the private key `d` in the second last one.
+use super::{PrivateKey, PublicKey};
+use crate::dsa::Signature;
+use crate::lattice::{default_delta, lll, Vector};
+use crate::math::{invmod, rand_range};
+use num_bigint::{BigInt, BigUint};
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+/// Sign the message hash `h` with a nonce whose low `bits` bits are zero.
+pub fn sign_biased(private: &PrivateKey, h: &BigUint, bits: usize) -> Signature {
+    let n = &private.curve.n;
+    loop {
+        let k = rand_range(&BigUint::one(), &(n >> bits)) << bits;
+        if let Some(sig) = private.sign_with_nonce(h, &k) {
+            return sig;
+        }
+    }
+}
+
+/// Turn a signature `sig` over the hash `h` into the pair `(t, u)` with `d * t - u = b mod q`,
+/// where `k = 2^bits * b`:
+///
+/// ```text
+/// t = r / (s * 2^bits) mod q
+/// u = -h / (s * 2^bits) mod q
+/// ```
+pub fn hnp_sample(
+    q: &BigUint,
+    h: &BigUint,
+    sig: &Signature,
+    bits: usize,
+) -> Option<(BigUint, BigUint)> {
+    let inverse = invmod(&(&sig.s << bits), q)?;
+    let t = &sig.r * &inverse % q;
+    let u = (q - h % q * &inverse % q) % q;
+    Some((t, u))
+}
+
+fn rational(n: &BigUint) -> BigRational {
+    BigRational::from_integer(BigInt::from(n.clone()))
+}
+
+/// The lattice for the hidden number problem `d * t_i - u_i = b_i mod q` with every `b_i` below
+/// `q / 2^bits`. For `n` samples its basis is `n + 2` vectors of `n + 2` coordinates:
+///
+/// ```text
+/// [ q   0  ... 0   0   0  ]
+/// [ 0   q  ... 0   0   0  ]
+///             ...
+/// [ 0   0  ... q   0   0  ]
+/// [ t1  t2 ... tn  ct  0  ]
+/// [ u1  u2 ... un  0   cu ]
+/// ```
+///
+/// with `ct = 1 / 2^bits` and `cu = q / 2^bits`. The combination `d * bt - bu` plus the right
+/// multiples of the first `n` rows is `[b1 ... bn, d * ct, -cu]`, which is about as short as the
+/// `b`s are, and much shorter than anything else in there.
+pub fn hnp_lattice(q: &BigUint, samples: &[(BigUint, BigUint)], bits: usize) -> Vec<Vector> {
+    let n = samples.len();
+    let scale = rational(&(BigUint::one() << bits));
+    let mut basis = (0..n)
+        .map(|i| {
+            let mut row = vec![BigRational::zero(); n + 2];
+            row[i] = rational(q);
+            row
+        })
+        .collect::<Vec<_>>();
+
+    let mut bt = samples.iter().map(|(t, _)| rational(t)).collect::<Vector>();
+    bt.push(BigRational::one() / &scale);
+    bt.push(BigRational::zero());
+    basis.push(bt);
+
+    let mut bu = samples.iter().map(|(_, u)| rational(u)).collect::<Vector>();
+    bu.push(BigRational::zero());
+    bu.push(rational(q) / &scale);
+    basis.push(bu);
+    basis
+}
+
+/// Solve the hidden number problem for `samples`, returning every candidate for `d` found in
+/// the reduced lattice: the second last coordinate, scaled back up, of each row whose last
+/// coordinate is `±cu`.
+pub fn hnp_candidates(q: &BigUint, samples: &[(BigUint, BigUint)], bits: usize) -> Vec<BigUint> {
+    let basis = hnp_lattice(q, samples, bits);
+    let cu = basis[basis.len() - 1][samples.len() + 1].clone();
+    let scale = rational(&(BigUint::one() << bits));
+    let q = BigInt::from(q.clone());
+
+    lll(&basis, &default_delta())
+        .iter()
+        .filter(|row| row[samples.len() + 1].abs() == cu)
+        .filter_map(|row| {
+            // the row might be the negation of the one we want
+            let mut d = &row[samples.len()] * &scale;
+            if row[samples.len() + 1] == cu {
+                d = -d;
+            }
+            if !d.is_integer() {
+                return None;
+            }
+            let d = d.to_integer();
+            Some(((d % &q + &q) % &q).to_biguint().expect("reduced mod q"))
+        })
+        .collect()
+}
+
+/// Recover the private key behind `public` from signatures over message hashes, all made with
+/// nonces whose low `bits` bits are zero. Something like 20 signatures are enough for a 125-bit
+/// curve with 8 bits of bias.
+pub fn recover_key(
+    public: &PublicKey,
+    signatures: &[(BigUint, Signature)],
+    bits: usize,
+) -> Option<BigUint> {
+    let q = &public.curve.n;
+    let samples = signatures
+        .iter()
+        .map(|(h, sig)| hnp_sample(q, h, sig, bits))
+        .collect::<Option<Vec<_>>>()?;
+    hnp_candidates(q, &samples, bits)
+        .into_iter()
+        .find(|d| public.curve.public(d) == public.q)
+}
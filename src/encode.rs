@@ -3,7 +3,8 @@
 /// Base64-related traits
 pub mod base64 {
 
-    const BASE64_MAP: &[u8; 65] =
+    /// The base64 alphabet, in order, followed by the padding character.
+    pub const BASE64_MAP: &[u8; 65] =
         b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=";
 
     /// Encode some bytes as a base64 `String`
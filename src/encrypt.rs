@@ -1,4 +1,6 @@
 use crate::transform::XorWith;
+use aes::block_cipher_trait::generic_array::GenericArray;
+use aes::block_cipher_trait::BlockCipher;
 use aes::Aes128;
 use block_modes::block_padding::ZeroPadding;
 use block_modes::{BlockMode, Ecb};
@@ -21,3 +23,163 @@ pub fn cbc_decrypt(key: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, ()> {
 
     Ok(cleartext)
 }
+
+/// The AES block size, in bytes.
+pub const BLOCK_SIZE: usize = 16;
+
+/// Indication that encryption or decryption has failed.
+#[derive(Debug, Eq, PartialEq)]
+pub enum EncryptError {
+    /// The key is not a valid AES-128 key.
+    KeyLength,
+    /// The IV is not exactly one block long.
+    IvLength,
+    /// The input is not a whole number of blocks.
+    BlockAlignment,
+    /// The authentication tag doesn't match the message.
+    Authentication,
+}
+
+/// Encrypt a single block in place with AES-128 under `key`.
+pub fn aes128_encrypt_block(key: &[u8], block: &mut [u8]) -> Result<(), EncryptError> {
+    if block.len() != BLOCK_SIZE {
+        return Err(EncryptError::BlockAlignment);
+    }
+    let cipher = Aes128::new_varkey(key).map_err(|_| EncryptError::KeyLength)?;
+    cipher.encrypt_block(GenericArray::from_mut_slice(block));
+    Ok(())
+}
+
+/// Decrypt a single block in place with AES-128 under `key`.
+pub fn aes128_decrypt_block(key: &[u8], block: &mut [u8]) -> Result<(), EncryptError> {
+    if block.len() != BLOCK_SIZE {
+        return Err(EncryptError::BlockAlignment);
+    }
+    let cipher = Aes128::new_varkey(key).map_err(|_| EncryptError::KeyLength)?;
+    cipher.decrypt_block(GenericArray::from_mut_slice(block));
+    Ok(())
+}
+
+/// Encrypt `plaintext` using `key` in CBC mode, starting from `iv`. The plaintext must already be
+/// padded to a whole number of blocks.
+pub fn cbc_encrypt_with_iv(
+    key: &[u8],
+    iv: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, EncryptError> {
+    if iv.len() != BLOCK_SIZE {
+        return Err(EncryptError::IvLength);
+    }
+    if plaintext.len() % BLOCK_SIZE != 0 {
+        return Err(EncryptError::BlockAlignment);
+    }
+
+    let cipher = Aes128::new_varkey(key).map_err(|_| EncryptError::KeyLength)?;
+    let mut prev = iv.to_vec();
+    let mut ciphertext = Vec::with_capacity(plaintext.len());
+    for block in plaintext.chunks(BLOCK_SIZE) {
+        let mut block = block.xor_with(&prev);
+        cipher.encrypt_block(GenericArray::from_mut_slice(&mut block));
+        ciphertext.extend_from_slice(&block);
+        prev = block;
+    }
+
+    Ok(ciphertext)
+}
+
+/// Decrypt `ciphertext` using `key` in CBC mode, starting from `iv`. No padding is removed.
+pub fn cbc_decrypt_with_iv(
+    key: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, EncryptError> {
+    if iv.len() != BLOCK_SIZE {
+        return Err(EncryptError::IvLength);
+    }
+    if ciphertext.len() % BLOCK_SIZE != 0 {
+        return Err(EncryptError::BlockAlignment);
+    }
+
+    let cipher = Aes128::new_varkey(key).map_err(|_| EncryptError::KeyLength)?;
+    let mut prev = iv;
+    let mut cleartext = Vec::with_capacity(ciphertext.len());
+    for block in ciphertext.chunks(BLOCK_SIZE) {
+        let mut clear_block = block.to_vec();
+        cipher.decrypt_block(GenericArray::from_mut_slice(&mut clear_block));
+        cleartext.extend(clear_block.xor_with(prev));
+        prev = block;
+    }
+
+    Ok(cleartext)
+}
+
+/// Encrypt or decrypt `input` using `key` in CTR mode.
+///
+/// The keystream is the encryption of a 64-bit little-endian `nonce` followed by a 64-bit
+/// little-endian block counter starting at zero.
+pub fn ctr_apply(key: &[u8], nonce: u64, input: &[u8]) -> Result<Vec<u8>, EncryptError> {
+    let cipher = Aes128::new_varkey(key).map_err(|_| EncryptError::KeyLength)?;
+    let mut output = Vec::with_capacity(input.len());
+    for (counter, chunk) in input.chunks(BLOCK_SIZE).enumerate() {
+        let mut keystream = nonce.to_le_bytes().to_vec();
+        keystream.extend_from_slice(&(counter as u64).to_le_bytes());
+        cipher.encrypt_block(GenericArray::from_mut_slice(&mut keystream));
+        output.extend(chunk.iter().zip(keystream.iter()).map(|(c, k)| c ^ k));
+    }
+    Ok(output)
+}
+
+/// The RC4 stream cipher.
+///
+/// ```
+/// use arse::encode::hex::ToHex;
+/// use arse::encrypt::Rc4;
+///
+/// let mut cipher = Rc4::new(b"Key").unwrap();
+/// assert_eq!(cipher.apply(b"Plaintext").to_hex(), "bbf316e8d940af0ad3");
+/// ```
+#[derive(Clone)]
+pub struct Rc4 {
+    s: [u8; 256],
+    i: u8,
+    j: u8,
+}
+
+impl Rc4 {
+    /// Run the key schedule for `key`, which must be between 1 and 256 bytes long.
+    pub fn new(key: &[u8]) -> Result<Self, EncryptError> {
+        if key.is_empty() || key.len() > 256 {
+            return Err(EncryptError::KeyLength);
+        }
+
+        let mut s = [0u8; 256];
+        for (i, b) in s.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        let mut j = 0u8;
+        for i in 0..256 {
+            j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
+            s.swap(i, j as usize);
+        }
+
+        Ok(Rc4 { s, i: 0, j: 0 })
+    }
+
+    /// Produce the next byte of keystream.
+    pub fn next_byte(&mut self) -> u8 {
+        self.i = self.i.wrapping_add(1);
+        self.j = self.j.wrapping_add(self.s[self.i as usize]);
+        self.s.swap(self.i as usize, self.j as usize);
+        self.s[self.s[self.i as usize].wrapping_add(self.s[self.j as usize]) as usize]
+    }
+
+    /// Encrypt or decrypt `input`, continuing from wherever the keystream left off.
+    pub fn apply(&mut self, input: &[u8]) -> Vec<u8> {
+        input.iter().map(|b| b ^ self.next_byte()).collect()
+    }
+}
+
+/// Encrypt or decrypt `input` with RC4 under `key`.
+pub fn rc4_apply(key: &[u8], input: &[u8]) -> Result<Vec<u8>, EncryptError> {
+    Ok(Rc4::new(key)?.apply(input))
+}
@@ -0,0 +1,138 @@
+/// AES-128 in Galois/Counter Mode: CTR mode encryption, authenticated with GHASH.
+///
+/// GHASH splits the associated data and the ciphertext into blocks, zero-padding each, appends a
+/// block holding both their lengths in bits, and treats the lot as GF(2^128) elements
+/// `b1 .. bn`. Its value is the polynomial `b1 * h^n + ... + bn * h` evaluated at the
+/// authentication key `h = E(K, 0)`. The tag masks that with `s = E(K, J0)`, where `J0` is the
+/// 96-bit nonce followed by a 32-bit counter of 1, and the message is encrypted in CTR mode with
+/// the counter starting from 2.
+use crate::encrypt::{EncryptError, BLOCK_SIZE};
+use crate::gf128::Gf128;
+use aes::block_cipher_trait::generic_array::GenericArray;
+use aes::block_cipher_trait::BlockCipher;
+use aes::Aes128;
+
+pub mod forbidden;
+pub mod truncated;
+
+/// The length of a GCM nonce, in bytes.
+pub const NONCE_SIZE: usize = 12;
+
+/// An encrypted message, its associated data, and the tag authenticating both.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Sealed {
+    pub aad: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    pub tag: [u8; BLOCK_SIZE],
+}
+
+/// The field elements GHASH runs over for `aad` and `ciphertext`: the blocks of each, then the
+/// length block.
+pub fn ghash_blocks(aad: &[u8], ciphertext: &[u8]) -> Vec<Gf128> {
+    let mut blocks = aad
+        .chunks(BLOCK_SIZE)
+        .chain(ciphertext.chunks(BLOCK_SIZE))
+        .map(Gf128::from_bytes)
+        .collect::<Vec<_>>();
+    let mut lengths = [0u8; BLOCK_SIZE];
+    lengths[..8].copy_from_slice(&(aad.len() as u64 * 8).to_be_bytes());
+    lengths[8..].copy_from_slice(&(ciphertext.len() as u64 * 8).to_be_bytes());
+    blocks.push(Gf128::from_block(&lengths));
+    blocks
+}
+
+/// GHASH `aad` and `ciphertext` under the authentication key `h`.
+pub fn ghash(h: Gf128, aad: &[u8], ciphertext: &[u8]) -> Gf128 {
+    ghash_blocks(aad, ciphertext)
+        .into_iter()
+        .fold(Gf128::ZERO, |acc, b| (acc + b) * h)
+}
+
+/// An AES-128-GCM key.
+///
+/// The all-zero test vectors from the GCM specification:
+///
+/// ```
+/// use arse::encode::hex::ToHex;
+/// use arse::gcm::Gcm;
+///
+/// let gcm = Gcm::new(&[0; 16]).unwrap();
+/// assert_eq!(gcm.seal(&[0; 12], b"", b"").tag.to_hex(), "58e2fccefa7e3061367f1d57a4e7455a");
+///
+/// let sealed = gcm.seal(&[0; 12], b"", &[0; 16]);
+/// assert_eq!((&sealed.ciphertext).to_hex(), "0388dace60b6a392f328c2b971b2fe78");
+/// assert_eq!(sealed.tag.to_hex(), "ab6e47d42cec13bdf53a67b21257bddf");
+/// assert_eq!(gcm.open(&[0; 12], &sealed).unwrap(), vec![0; 16]);
+/// ```
+#[derive(Clone)]
+pub struct Gcm {
+    cipher: Aes128,
+    h: Gf128,
+}
+
+impl Gcm {
+    pub fn new(key: &[u8]) -> Result<Self, EncryptError> {
+        let cipher = Aes128::new_varkey(key).map_err(|_| EncryptError::KeyLength)?;
+        let mut block = [0u8; BLOCK_SIZE];
+        cipher.encrypt_block(GenericArray::from_mut_slice(&mut block));
+        Ok(Gcm {
+            cipher,
+            h: Gf128::from_block(&block),
+        })
+    }
+
+    /// The authentication key `h = E(K, 0)`, which is meant to stay secret.
+    pub fn auth_key(&self) -> Gf128 {
+        self.h
+    }
+
+    /// Encrypt the counter block for `nonce` and `counter`.
+    fn keystream_block(&self, nonce: &[u8; NONCE_SIZE], counter: u32) -> [u8; BLOCK_SIZE] {
+        let mut block = [0u8; BLOCK_SIZE];
+        block[..NONCE_SIZE].copy_from_slice(nonce);
+        block[NONCE_SIZE..].copy_from_slice(&counter.to_be_bytes());
+        self.cipher
+            .encrypt_block(GenericArray::from_mut_slice(&mut block));
+        block
+    }
+
+    /// Encrypt or decrypt `input` in CTR mode, counting from 2.
+    fn ctr_apply(&self, nonce: &[u8; NONCE_SIZE], input: &[u8]) -> Vec<u8> {
+        input
+            .chunks(BLOCK_SIZE)
+            .zip(2..)
+            .flat_map(|(chunk, counter)| {
+                let keystream = self.keystream_block(nonce, counter);
+                chunk
+                    .iter()
+                    .zip(keystream.iter())
+                    .map(|(c, k)| c ^ k)
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// The full tag for `aad` and `ciphertext` under `nonce`.
+    pub fn tag(&self, nonce: &[u8; NONCE_SIZE], aad: &[u8], ciphertext: &[u8]) -> [u8; BLOCK_SIZE] {
+        let s = Gf128::from_block(&self.keystream_block(nonce, 1));
+        (ghash(self.h, aad, ciphertext) + s).to_block()
+    }
+
+    /// Encrypt `plaintext` under `nonce` and authenticate it along with `aad`.
+    pub fn seal(&self, nonce: &[u8; NONCE_SIZE], aad: &[u8], plaintext: &[u8]) -> Sealed {
+        let ciphertext = self.ctr_apply(nonce, plaintext);
+        Sealed {
+            aad: aad.to_vec(),
+            tag: self.tag(nonce, aad, &ciphertext),
+            ciphertext,
+        }
+    }
+
+    /// Check the tag on `sealed`, and decrypt it if it's good.
+    pub fn open(&self, nonce: &[u8; NONCE_SIZE], sealed: &Sealed) -> Result<Vec<u8>, EncryptError> {
+        if self.tag(nonce, &sealed.aad, &sealed.ciphertext) != sealed.tag {
+            return Err(EncryptError::Authentication);
+        }
+        Ok(self.ctr_apply(nonce, &sealed.ciphertext))
+    }
+}
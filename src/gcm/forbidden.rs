@@ -0,0 +1,56 @@
+/// The "forbidden attack" on GCM: recovering the authentication key from two messages sealed
+/// under the same nonce.
+///
+/// A tag is `t = g(h) + s`, where `g` is the GHASH polynomial of the message and `s` depends
+/// only on the key and nonce. Move `t` into `g`'s constant term and the polynomial has `h` as a
+/// root of `g(x) + t = s`; do the same for a second message under the same nonce and add them,
+/// and `s` cancels out, leaving a known polynomial with `h` among its roots. There are only as
+/// many roots as its degree, and once `h` is known, `s` follows from either tag and any message
+/// at all can be given a valid tag under that nonce.
+use super::{ghash, ghash_blocks, Sealed};
+use crate::gf128::poly::Poly;
+use crate::gf128::Gf128;
+
+/// The GHASH polynomial of `sealed` with its tag added to the constant term, whose value at the
+/// authentication key is the tag mask `s`.
+pub fn tag_polynomial(sealed: &Sealed) -> Poly {
+    let blocks = ghash_blocks(&sealed.aad, &sealed.ciphertext);
+    let n = blocks.len();
+    let mut coeffs = vec![Gf128::ZERO; n + 1];
+    coeffs[0] = Gf128::from_block(&sealed.tag);
+    for (i, b) in blocks.into_iter().enumerate() {
+        coeffs[n - i] = b;
+    }
+    Poly::new(coeffs)
+}
+
+/// Every candidate for the authentication key given two messages sealed under the same key and
+/// nonce: the roots of the sum of their tag polynomials.
+pub fn candidate_keys(a: &Sealed, b: &Sealed) -> Vec<Gf128> {
+    (&tag_polynomial(a) + &tag_polynomial(b)).roots()
+}
+
+/// Tag `aad` and `ciphertext` for the nonce that `known` was sealed under, assuming `h` is the
+/// authentication key.
+pub fn forge(h: Gf128, known: &Sealed, aad: &[u8], ciphertext: &[u8]) -> Sealed {
+    let s = Gf128::from_block(&known.tag) + ghash(h, &known.aad, &known.ciphertext);
+    Sealed {
+        aad: aad.to_vec(),
+        ciphertext: ciphertext.to_vec(),
+        tag: (ghash(h, aad, ciphertext) + s).to_block(),
+    }
+}
+
+/// Recover the authentication key from two messages sealed under the same key and nonce, using
+/// `oracle`, which says whether a message is authentic, to pick the right one out of the
+/// candidates by trying a forgery with each.
+pub fn recover_key<F>(a: &Sealed, b: &Sealed, mut oracle: F) -> Option<Gf128>
+where
+    F: FnMut(&Sealed) -> bool,
+{
+    let mut ciphertext = a.ciphertext.clone();
+    ciphertext.push(0);
+    candidate_keys(a, b)
+        .into_iter()
+        .find(|&h| oracle(&forge(h, a, &a.aad, &ciphertext)))
+}
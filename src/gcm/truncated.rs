@@ -0,0 +1,203 @@
+/// Ferguson's attack on GCM with truncated tags, recovering the authentication key a few bits
+/// at a time.
+///
+/// Squaring is linear in GF(2^128), so changing the ciphertext block that GHASH multiplies by
+/// `h^(2^i)` by `d_i` changes the tag by `d_i * h^(2^i) = M(d_i) * S^i * h`, where `M(d_i)` is
+/// the matrix of multiplication by `d_i` and `S` the matrix of squaring. Changing several such
+/// blocks at once changes the tag by `Ad * h`, with `Ad` the sum of those matrices, and each bit
+/// of `Ad` is linear in the bits of the `d_i`. So we can solve for changes that zero out the
+/// first few rows of `Ad`, leaving that many bits of the tag untouched whatever `h` is, and only
+/// need to get lucky on the rest. When a forgery is accepted, the remaining rows of `Ad` must
+/// have been orthogonal to `h` as well, and those are new equations in `h`.
+///
+/// Every equation learned shrinks the space `h` can be in, so fewer unknowns are left to zero
+/// out in each row and more rows can be zeroed at a time. The forgeries get likelier as the
+/// attack goes on, until only one nonzero `h` is left.
+use super::{ghash_blocks, Gcm, Sealed, NONCE_SIZE};
+use crate::encrypt::BLOCK_SIZE;
+use crate::gf128::{Gf128, MulTable};
+use crate::gf2::{BitMatrix, BitVector};
+use rand::{random, thread_rng, RngCore};
+
+/// The tag length the oracle checks, in bytes.
+pub const TAG_BYTES: usize = 4;
+
+/// The number of blocks in the challenge's messages, enough for 17 blocks at power-of-two
+/// positions.
+pub const MESSAGE_BLOCKS: usize = 1 << 17;
+
+/// A receiver that seals messages under a key and nonce of its own, keeping only the first
+/// `tag_bytes` of each tag, and answers whether a message it's given is authentic.
+///
+/// The attack asks it about a great many long messages, so it keeps multiplication by the
+/// authentication key tabulated rather than multiplying bit by bit like `Gcm`.
+pub struct Oracle {
+    gcm: Gcm,
+    table: MulTable,
+    nonce: [u8; NONCE_SIZE],
+    tag_bytes: usize,
+}
+
+impl Oracle {
+    /// An oracle with a random key and nonce, checking tags `tag_bytes` long.
+    pub fn new(tag_bytes: usize) -> Self {
+        let mut key = [0u8; 16];
+        thread_rng().fill_bytes(&mut key);
+        let gcm = Gcm::new(&key).expect("valid key length");
+        Oracle {
+            table: MulTable::new(gcm.h),
+            gcm,
+            nonce: random(),
+            tag_bytes,
+        }
+    }
+
+    /// The authentication key, to check the attack's answer against.
+    pub fn auth_key(&self) -> Gf128 {
+        self.gcm.auth_key()
+    }
+
+    /// Seal `plaintext`, with the tag truncated and the rest of it zeroed.
+    pub fn seal(&self, plaintext: &[u8]) -> Sealed {
+        let mut sealed = self.gcm.seal(&self.nonce, &[], plaintext);
+        sealed.tag[self.tag_bytes..].iter_mut().for_each(|b| *b = 0);
+        sealed
+    }
+
+    /// Check the first `tag_bytes` of the tag on `sealed`.
+    pub fn verify(&self, sealed: &Sealed) -> bool {
+        let s = Gf128::from_block(&self.gcm.keystream_block(&self.nonce, 1));
+        let tag = ghash_blocks(&sealed.aad, &sealed.ciphertext)
+            .into_iter()
+            .fold(Gf128::ZERO, |acc, b| self.table.mul(acc + b))
+            + s;
+        tag.to_block()[..self.tag_bytes] == sealed.tag[..self.tag_bytes]
+    }
+}
+
+/// The matrices `S^1 .. S^n` of squaring once to `n` times.
+pub fn squaring_powers(n: usize) -> Vec<BitMatrix> {
+    let s = Gf128::squaring_matrix();
+    let mut powers = vec![s.clone()];
+    while powers.len() < n {
+        let next = s.mul(&powers[powers.len() - 1]);
+        powers.push(next);
+    }
+    powers
+}
+
+/// The matrix `Ad` for the changes `d`, where `d[i]` is added to the block multiplied by
+/// `h^(2^(i + 1))`: the sum of `M(d[i]) * S^(i + 1)`.
+pub fn error_matrix(d: &[Gf128], squarings: &[BitMatrix]) -> BitMatrix {
+    let mut ad = BitMatrix::zero(128, 128);
+    for (d, s) in d.iter().zip(squarings) {
+        ad ^= &d.mul_matrix().mul(s);
+    }
+    ad
+}
+
+/// The dependency matrix `T` of the first `rows` rows of `Ad * X` on the changes `d`, where the
+/// columns of `X` span the space that `h` is known to be in. Multiplying `T` by the bits of all
+/// the `d[i]` strung together gives those rows strung together, so its kernel is every change
+/// that leaves them zero.
+pub fn dependency_matrix(rows: usize, x: &BitMatrix, squarings: &[BitMatrix]) -> BitMatrix {
+    let m = x.num_cols();
+    let mut columns = Vec::with_capacity(squarings.len() * 128);
+    for s in squarings {
+        // flipping bit b of d[i] adds x^b * S^(i + 1) * X to Ad * X
+        let sx = s.mul(x).transpose();
+        let sx = sx.rows().iter().map(Gf128::from_bits).collect::<Vec<_>>();
+        for b in 0..128 {
+            let mut column = BitVector::zero(rows * m);
+            for (c, &p) in sx.iter().enumerate() {
+                let product = (Gf128(1 << b) * p).0;
+                for r in 0..rows {
+                    if (product >> r) & 1 == 1 {
+                        column.set(r * m + c, true);
+                    }
+                }
+            }
+            columns.push(column);
+        }
+    }
+    BitMatrix::from_columns(rows * m, &columns)
+}
+
+/// A random nonzero combination of `basis`, split into field elements.
+fn random_change(basis: &[BitVector]) -> Vec<Gf128> {
+    loop {
+        let mut v = BitVector::zero(basis[0].len());
+        for b in basis {
+            if random() {
+                v ^= b;
+            }
+        }
+        if !v.is_zero() {
+            return v
+                .words()
+                .chunks(2)
+                .map(|w| Gf128(u128::from(w[0]) | u128::from(w[1]) << 64))
+                .collect();
+        }
+    }
+}
+
+/// Add `d[i]` to the ciphertext block that GHASH multiplies by `h^(2^(i + 1))`. There are no
+/// associated data, so the last ciphertext block is multiplied by `h^2`, the one before it by
+/// `h^3`, and so on.
+fn apply_change(ciphertext: &mut [u8], d: &[Gf128]) {
+    let blocks = ciphertext.len() / BLOCK_SIZE;
+    for (i, d) in d.iter().enumerate() {
+        let index = blocks + 1 - (1 << (i + 1));
+        let block = &mut ciphertext[index * BLOCK_SIZE..(index + 1) * BLOCK_SIZE];
+        for (c, d) in block.iter_mut().zip(d.to_block().iter()) {
+            *c ^= d;
+        }
+    }
+}
+
+/// Recover the authentication key from `sealed`, a message with no associated data and a whole
+/// number of blocks, using `oracle` to check forgeries whose tags are `tag_bytes` long.
+pub fn recover_key<F>(sealed: &Sealed, tag_bytes: usize, mut oracle: F) -> Option<Gf128>
+where
+    F: FnMut(&Sealed) -> bool,
+{
+    let blocks = sealed.ciphertext.len() / BLOCK_SIZE;
+    if !sealed.aad.is_empty() || sealed.ciphertext.len() % BLOCK_SIZE != 0 || blocks == 0 {
+        return None;
+    }
+    // the number of blocks multiplied by h^(2^i) for some i >= 1
+    let n = (blocks + 1).ilog2() as usize;
+    let tag_bits = tag_bytes * 8;
+    let squarings = squaring_powers(n);
+
+    let mut known = BitMatrix::zero(0, 128);
+    let mut forged = sealed.clone();
+    loop {
+        let basis = known.kernel();
+        match basis.len() {
+            0 => return None,
+            1 => return Some(Gf128::from_bits(&basis[0])),
+            _ => {}
+        }
+
+        // zero as many rows as there are degrees of freedom for, but always leave one to learn
+        let m = basis.len();
+        let rows = ((n * 128 - 1) / m).min(tag_bits - 1);
+        let x = BitMatrix::from_columns(128, &basis);
+        let changes = dependency_matrix(rows, &x, &squarings).kernel();
+        loop {
+            let d = random_change(&changes);
+            apply_change(&mut forged.ciphertext, &d);
+            let accepted = oracle(&forged);
+            apply_change(&mut forged.ciphertext, &d);
+            if accepted {
+                let ad = error_matrix(&d, &squarings);
+                ad.rows()[rows..tag_bits]
+                    .iter()
+                    .for_each(|row| known.push_row(row.clone()));
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+/// The field GF(2^128), as used by GCM, and polynomials over it.
+///
+/// Elements are polynomials over GF(2) modulo `x^128 + x^7 + x^2 + x + 1`. GCM writes them as
+/// 16-byte blocks with the coefficient of `x^0` in the most significant bit of the first byte,
+/// which is backwards from the usual way of packing bits into an integer, so here the
+/// coefficient of `x^i` is bit `i` of a `u128`, and the conversions to and from blocks reverse
+/// the bits.
+use crate::gf2::{BitMatrix, BitVector};
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Sub};
+
+pub mod poly;
+
+/// The low terms of the modulus, `x^7 + x^2 + x + 1`, which `x^128` reduces to.
+const REDUCTION: u128 = 0x87;
+
+/// An element of GF(2^128).
+///
+/// ```
+/// use arse::gf128::Gf128;
+///
+/// let a = Gf128::from_block(b"YELLOW SUBMARINE");
+/// assert_eq!(a * a.inverse().unwrap(), Gf128::ONE);
+/// assert_eq!(a + a, Gf128::ZERO);
+/// assert_eq!(a.to_block(), *b"YELLOW SUBMARINE");
+/// ```
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub struct Gf128(pub u128);
+
+impl Gf128 {
+    pub const ZERO: Gf128 = Gf128(0);
+    pub const ONE: Gf128 = Gf128(1);
+
+    /// Read an element from a GCM block.
+    pub fn from_block(block: &[u8; 16]) -> Self {
+        Gf128(u128::from_be_bytes(*block).reverse_bits())
+    }
+
+    /// Read an element from up to 16 bytes, padding with zeroes on the right.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut block = [0u8; 16];
+        block[..bytes.len()].copy_from_slice(bytes);
+        Gf128::from_block(&block)
+    }
+
+    /// Write this element as a GCM block.
+    pub fn to_block(self) -> [u8; 16] {
+        self.0.reverse_bits().to_be_bytes()
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Raise this element to the power `2^k`, by squaring `k` times.
+    pub fn pow2k(self, k: usize) -> Self {
+        (0..k).fold(self, |a, _| a * a)
+    }
+
+    /// Raise this element to the power `e`.
+    pub fn pow(self, e: u128) -> Self {
+        let mut result = Gf128::ONE;
+        for i in (0..128).rev() {
+            result = result * result;
+            if (e >> i) & 1 == 1 {
+                result *= self;
+            }
+        }
+        result
+    }
+
+    /// The multiplicative inverse `a^(2^128 - 2)`, or `None` for zero.
+    pub fn inverse(self) -> Option<Self> {
+        if self.is_zero() {
+            None
+        } else {
+            Some(self.pow(u128::MAX - 1))
+        }
+    }
+
+    /// The unique square root `a^(2^127)`.
+    pub fn sqrt(self) -> Self {
+        self.pow2k(127)
+    }
+
+    /// The coefficients as a vector of 128 bits, that of `x^i` at index `i`.
+    pub fn to_bits(self) -> BitVector {
+        BitVector::from_words(128, &[self.0 as u64, (self.0 >> 64) as u64])
+    }
+
+    /// The element with the coefficients in `bits`, a vector of 128 bits.
+    pub fn from_bits(bits: &BitVector) -> Self {
+        assert_eq!(bits.len(), 128);
+        let words = bits.words();
+        Gf128(u128::from(words[0]) | u128::from(words[1]) << 64)
+    }
+
+    /// The matrix of multiplication by this element: `a.mul_matrix() * b.to_bits()` is
+    /// `(a * b).to_bits()`. Its column `i` is `a * x^i`.
+    ///
+    /// ```
+    /// use arse::gf128::Gf128;
+    ///
+    /// let (a, b) = (Gf128(0x1234_5678), Gf128(u128::MAX / 3));
+    /// assert_eq!(Gf128::from_bits(&a.mul_matrix().mul_vec(&b.to_bits())), a * b);
+    /// ```
+    pub fn mul_matrix(self) -> BitMatrix {
+        let columns = (0..128)
+            .map(|i| (self * Gf128(1 << i)).to_bits())
+            .collect::<Vec<_>>();
+        BitMatrix::from_columns(128, &columns)
+    }
+
+    /// The matrix of squaring, which is linear in characteristic 2 since the cross terms of
+    /// `(a + b)^2` cancel. Its column `i` is `x^(2i)`.
+    ///
+    /// ```
+    /// use arse::gf128::Gf128;
+    ///
+    /// let a = Gf128(0xdead_beef << 70 | 0xcafe);
+    /// assert_eq!(Gf128::from_bits(&Gf128::squaring_matrix().mul_vec(&a.to_bits())), a * a);
+    /// ```
+    pub fn squaring_matrix() -> BitMatrix {
+        let columns = (0..128)
+            .map(|i| Gf128(1 << i).pow2k(1).to_bits())
+            .collect::<Vec<_>>();
+        BitMatrix::from_columns(128, &columns)
+    }
+}
+
+/// Multiplication by a fixed element, by table lookup.
+///
+/// The product is linear in the other operand, so it's the sum of the products with each of that
+/// operand's bytes in place, and there are only 16 * 256 of those to tabulate. That's a lot
+/// quicker than multiplying bit by bit when the same element gets used over and over, as the
+/// authentication key does in GHASH.
+///
+/// ```
+/// use arse::gf128::{Gf128, MulTable};
+///
+/// let (a, b) = (Gf128(0x0123_4567_89ab_cdef << 50), Gf128(u128::MAX / 5));
+/// assert_eq!(MulTable::new(a).mul(b), a * b);
+/// ```
+#[derive(Clone)]
+pub struct MulTable(Vec<[Gf128; 256]>);
+
+impl MulTable {
+    pub fn new(a: Gf128) -> Self {
+        let tables = (0..16)
+            .map(|byte| {
+                let mut table = [Gf128::ZERO; 256];
+                for v in 1..256usize {
+                    // v with its lowest bit cleared has already been filled in
+                    let low = v.trailing_zeros();
+                    table[v] = table[v & (v - 1)] + a * Gf128(1 << (8 * byte + low));
+                }
+                table
+            })
+            .collect();
+        MulTable(tables)
+    }
+
+    /// Multiply `b` by the fixed element.
+    pub fn mul(&self, b: Gf128) -> Gf128 {
+        b.0.to_le_bytes()
+            .iter()
+            .zip(&self.0)
+            .fold(Gf128::ZERO, |acc, (&byte, table)| {
+                acc + table[byte as usize]
+            })
+    }
+}
+
+impl fmt::Debug for Gf128 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Gf128({:032x})", self.0)
+    }
+}
+
+/// Addition is XOR.
+impl Add for Gf128 {
+    type Output = Gf128;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn add(self, other: Gf128) -> Gf128 {
+        Gf128(self.0 ^ other.0)
+    }
+}
+
+impl AddAssign for Gf128 {
+    fn add_assign(&mut self, other: Gf128) {
+        *self = *self + other;
+    }
+}
+
+/// Subtraction is the same as addition in characteristic 2.
+impl Sub for Gf128 {
+    type Output = Gf128;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, other: Gf128) -> Gf128 {
+        self + other
+    }
+}
+
+/// Carry-less multiplication, reducing as it goes.
+impl Mul for Gf128 {
+    type Output = Gf128;
+
+    fn mul(self, other: Gf128) -> Gf128 {
+        let (mut a, mut b) = (self.0, other.0);
+        let mut product = 0u128;
+        while b != 0 {
+            if b & 1 == 1 {
+                product ^= a;
+            }
+            b >>= 1;
+            // a = a * x, folding x^128 back in
+            let carry = a >> 127;
+            a <<= 1;
+            if carry == 1 {
+                a ^= REDUCTION;
+            }
+        }
+        Gf128(product)
+    }
+}
+
+impl MulAssign for Gf128 {
+    fn mul_assign(&mut self, other: Gf128) {
+        *self = *self * other;
+    }
+}
+
+/// Division by a nonzero element.
+impl Div for Gf128 {
+    type Output = Gf128;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Gf128) -> Gf128 {
+        self * other.inverse().expect("division by zero")
+    }
+}
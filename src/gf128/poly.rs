@@ -0,0 +1,294 @@
+/// Polynomials with coefficients in GF(2^128), and factoring them.
+///
+/// Factoring goes the usual way: split off repeated factors with a square-free factorization,
+/// group what's left by the degree of its irreducible factors with a distinct-degree
+/// factorization, and then break each group apart with a randomized equal-degree factorization.
+/// Root finding only needs the degree-one factors, which is all the GCM attacks want.
+use super::Gf128;
+use rand::random;
+use std::ops::{Add, Mul};
+
+/// The number of squarings that make up the Frobenius map `a -> a^q` for `q = 2^128`.
+const FROBENIUS_SQUARINGS: usize = 128;
+
+/// A polynomial over GF(2^128), its coefficients stored lowest degree first with no zero
+/// leading coefficient.
+///
+/// ```
+/// use arse::gf128::{poly::Poly, Gf128};
+///
+/// // (x + 1)(x + 2) = x^2 + 3x + 2
+/// let (one, two) = (Gf128(1), Gf128(2));
+/// let p = &Poly::new(vec![one, Gf128::ONE]) * &Poly::new(vec![two, Gf128::ONE]);
+/// assert_eq!(p, Poly::new(vec![Gf128(2), Gf128(3), Gf128::ONE]));
+/// let mut roots = p.roots();
+/// roots.sort_by_key(|r| r.0);
+/// assert_eq!(roots, vec![one, two]);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Poly(Vec<Gf128>);
+
+impl Poly {
+    /// The polynomial with coefficients `coeffs`, lowest degree first.
+    pub fn new(mut coeffs: Vec<Gf128>) -> Self {
+        while coeffs.last().is_some_and(|c| c.is_zero()) {
+            coeffs.pop();
+        }
+        Poly(coeffs)
+    }
+
+    pub fn zero() -> Self {
+        Poly(Vec::new())
+    }
+
+    pub fn one() -> Self {
+        Poly(vec![Gf128::ONE])
+    }
+
+    /// The polynomial `x`.
+    pub fn x() -> Self {
+        Poly(vec![Gf128::ZERO, Gf128::ONE])
+    }
+
+    /// The coefficients, lowest degree first.
+    pub fn coeffs(&self) -> &[Gf128] {
+        &self.0
+    }
+
+    /// The degree, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.0.len().checked_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.0 == [Gf128::ONE]
+    }
+
+    /// The leading coefficient, zero for the zero polynomial.
+    pub fn leading(&self) -> Gf128 {
+        self.0.last().cloned().unwrap_or_default()
+    }
+
+    /// Evaluate at `x` by Horner's rule.
+    pub fn eval(&self, x: Gf128) -> Gf128 {
+        self.0.iter().rev().fold(Gf128::ZERO, |acc, &c| acc * x + c)
+    }
+
+    /// Multiply every coefficient by `k`.
+    pub fn scale(&self, k: Gf128) -> Self {
+        Poly::new(self.0.iter().map(|&c| c * k).collect())
+    }
+
+    /// Scale to a leading coefficient of one. The zero polynomial stays zero.
+    pub fn monic(&self) -> Self {
+        match self.leading().inverse() {
+            Some(inverse) => self.scale(inverse),
+            None => Poly::zero(),
+        }
+    }
+
+    /// Divide by `divisor`, returning the quotient and remainder.
+    ///
+    /// Panics if `divisor` is zero.
+    pub fn divmod(&self, divisor: &Poly) -> (Poly, Poly) {
+        let d = divisor.degree().expect("division by the zero polynomial");
+        let inverse = divisor.leading().inverse().expect("nonzero leading term");
+        let mut remainder = self.0.clone();
+        if remainder.len() <= d {
+            return (Poly::zero(), self.clone());
+        }
+
+        let mut quotient = vec![Gf128::ZERO; remainder.len() - d];
+        for i in (0..quotient.len()).rev() {
+            let q = remainder[i + d] * inverse;
+            quotient[i] = q;
+            for (r, &c) in remainder[i..=i + d].iter_mut().zip(&divisor.0) {
+                *r += q * c;
+            }
+        }
+        remainder.truncate(d);
+        (Poly::new(quotient), Poly::new(remainder))
+    }
+
+    /// The remainder after dividing by `divisor`.
+    pub fn rem(&self, divisor: &Poly) -> Poly {
+        self.divmod(divisor).1
+    }
+
+    /// The monic greatest common divisor of `self` and `other`.
+    pub fn gcd(&self, other: &Poly) -> Poly {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let r = a.rem(&b);
+            a = b;
+            b = r;
+        }
+        a.monic()
+    }
+
+    /// The formal derivative. In characteristic 2 the even-degree terms all vanish.
+    pub fn derivative(&self) -> Poly {
+        Poly::new(
+            self.0
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, &c)| if i % 2 == 1 { c } else { Gf128::ZERO })
+                .collect(),
+        )
+    }
+
+    /// Raise to the power `2^k` mod `modulus`, by squaring `k` times.
+    pub fn pow2k_mod(&self, k: usize, modulus: &Poly) -> Poly {
+        (0..k).fold(self.rem(modulus), |a, _| (&a * &a).rem(modulus))
+    }
+
+    /// The square root of a polynomial whose derivative is zero, which in characteristic 2 means
+    /// every odd-degree coefficient is zero: take the square root of each even one.
+    fn sqrt(&self) -> Poly {
+        Poly::new(self.0.iter().step_by(2).map(|c| c.sqrt()).collect())
+    }
+
+    /// Factor a monic polynomial into square-free parts, returning each with its multiplicity.
+    /// Multiplying `f^i` over all the pairs `(f, i)` gives back the original.
+    pub fn square_free_factorization(&self) -> Vec<(Poly, usize)> {
+        let mut factors = Vec::new();
+        if self.degree().is_none_or(|d| d == 0) {
+            return factors;
+        }
+
+        let mut c = self.gcd(&self.derivative());
+        let mut w = self.divmod(&c).0;
+        let mut i = 1;
+        while !w.is_one() {
+            let y = w.gcd(&c);
+            let factor = w.divmod(&y).0;
+            if !factor.is_one() {
+                factors.push((factor, i));
+            }
+            w = y;
+            c = c.divmod(&w).0;
+            i += 1;
+        }
+
+        // whatever's left is a perfect square
+        if !c.is_one() {
+            factors.extend(
+                c.sqrt()
+                    .square_free_factorization()
+                    .into_iter()
+                    .map(|(f, i)| (f, i * 2)),
+            );
+        }
+        factors
+    }
+
+    /// Split a monic square-free polynomial into parts that are each the product of all its
+    /// irreducible factors of one degree, returning each with that degree.
+    ///
+    /// The roots of `x^(q^i) - x` are the elements of the degree `i` extension, so its gcd with
+    /// what's left after the lower degrees are removed is the product of the degree `i` factors.
+    pub fn distinct_degree_factorization(&self) -> Vec<(Poly, usize)> {
+        let mut factors = Vec::new();
+        let mut f = self.clone();
+        let mut h = Poly::x();
+        let mut i = 1;
+        while f.degree().is_some_and(|d| d >= 2 * i) {
+            h = h.pow2k_mod(FROBENIUS_SQUARINGS, &f);
+            let g = f.gcd(&(&h + &Poly::x()));
+            if !g.is_one() {
+                f = f.divmod(&g).0;
+                h = h.rem(&f);
+                factors.push((g, i));
+            }
+            i += 1;
+        }
+        if let Some(d) = f.degree() {
+            if d > 0 {
+                factors.push((f, d));
+            }
+        }
+        factors
+    }
+
+    /// Split a monic square-free polynomial whose irreducible factors all have degree `d` into
+    /// those factors, by Cantor-Zassenhaus.
+    ///
+    /// The odd-characteristic version raises a random polynomial to `(q^d - 1) / 2`; in
+    /// characteristic 2 the trace `a + a^2 + a^4 + ... + a^(2^(128d - 1))` does the same job,
+    /// being 0 mod about half the factors and 1 mod the rest.
+    pub fn equal_degree_factorization(&self, d: usize) -> Vec<Poly> {
+        let n = match self.degree() {
+            Some(n) if n > d => n,
+            Some(0) | None => return Vec::new(),
+            Some(_) => return vec![self.clone()],
+        };
+
+        loop {
+            let a = Poly::new((0..n).map(|_| Gf128(random())).collect());
+            let mut term = a.rem(self);
+            let mut trace = term.clone();
+            for _ in 1..FROBENIUS_SQUARINGS * d {
+                term = (&term * &term).rem(self);
+                trace = &trace + &term;
+            }
+            let g = self.gcd(&trace);
+            if g.degree().is_some_and(|k| k > 0 && k < n) {
+                let mut factors = g.equal_degree_factorization(d);
+                factors.extend(self.divmod(&g).0.equal_degree_factorization(d));
+                return factors;
+            }
+        }
+    }
+
+    /// The distinct roots of this polynomial in GF(2^128).
+    pub fn roots(&self) -> Vec<Gf128> {
+        self.monic()
+            .square_free_factorization()
+            .into_iter()
+            .flat_map(|(f, _)| f.distinct_degree_factorization())
+            .filter(|(_, d)| *d == 1)
+            .flat_map(|(f, _)| f.equal_degree_factorization(1))
+            // a monic linear factor x + c has the root c, since -c = c
+            .map(|f| f.0[0])
+            .collect()
+    }
+}
+
+impl<'a> Add<&'a Poly> for &'a Poly {
+    type Output = Poly;
+
+    fn add(self, other: &Poly) -> Poly {
+        let (long, short) = if self.0.len() >= other.0.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut sum = long.0.clone();
+        for (s, &c) in sum.iter_mut().zip(&short.0) {
+            *s += c;
+        }
+        Poly::new(sum)
+    }
+}
+
+impl<'a> Mul<&'a Poly> for &'a Poly {
+    type Output = Poly;
+
+    fn mul(self, other: &Poly) -> Poly {
+        if self.is_zero() || other.is_zero() {
+            return Poly::zero();
+        }
+        let mut product = vec![Gf128::ZERO; self.0.len() + other.0.len() - 1];
+        for (i, &a) in self.0.iter().enumerate() {
+            for (p, &b) in product[i..].iter_mut().zip(&other.0) {
+                *p += a * b;
+            }
+        }
+        Poly::new(product)
+    }
+}
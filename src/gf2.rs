@@ -0,0 +1,299 @@
+/// Vectors and matrices over GF(2), packed 64 bits to a word.
+///
+/// Anything linear over GF(2) fits in here, and GF(2^128) has plenty of that: multiplication by
+/// a constant and squaring are both linear maps on its 128 bits, so they become 128 by 128 bit
+/// matrices, and questions about them become Gaussian elimination.
+use std::ops::BitXorAssign;
+
+const WORD_BITS: usize = 64;
+
+/// A vector of bits.
+///
+/// ```
+/// use arse::gf2::BitVector;
+///
+/// let mut v = BitVector::zero(100);
+/// v.set(3, true);
+/// v.set(99, true);
+/// let mut w = v.clone();
+/// w.set(42, true);
+/// assert!(!v.dot(&w));
+/// w ^= &v;
+/// assert_eq!(w.ones().collect::<Vec<_>>(), vec![42]);
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BitVector {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl BitVector {
+    /// The zero vector of length `len`.
+    pub fn zero(len: usize) -> Self {
+        BitVector {
+            len,
+            words: vec![0; len.div_ceil(WORD_BITS)],
+        }
+    }
+
+    /// The vector holding the low `len` bits of `words`, least significant first.
+    pub fn from_words(len: usize, words: &[u64]) -> Self {
+        let mut v = BitVector::zero(len);
+        let n = v.words.len();
+        v.words.copy_from_slice(&words[..n]);
+        v.clear_excess();
+        v
+    }
+
+    /// Keep the unused bits of the last word zero, so that comparisons work.
+    fn clear_excess(&mut self) {
+        let excess = self.words.len() * WORD_BITS - self.len;
+        if let Some(last) = self.words.last_mut() {
+            *last &= u64::MAX >> excess;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The packed bits, least significant first.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len);
+        (self.words[i / WORD_BITS] >> (i % WORD_BITS)) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, bit: bool) {
+        assert!(i < self.len);
+        let mask = 1 << (i % WORD_BITS);
+        if bit {
+            self.words[i / WORD_BITS] |= mask;
+        } else {
+            self.words[i / WORD_BITS] &= !mask;
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// The inner product, which is the parity of the bits the two have in common.
+    pub fn dot(&self, other: &BitVector) -> bool {
+        assert_eq!(self.len, other.len);
+        self.words
+            .iter()
+            .zip(&other.words)
+            .fold(0, |acc, (a, b)| acc ^ (a & b).count_ones())
+            & 1
+            == 1
+    }
+
+    /// The indices of the set bits, in increasing order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(move |&i| self.get(i))
+    }
+}
+
+/// Addition is XOR.
+impl BitXorAssign<&BitVector> for BitVector {
+    fn bitxor_assign(&mut self, other: &BitVector) {
+        assert_eq!(self.len, other.len);
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a ^= b;
+        }
+    }
+}
+
+/// A matrix of bits, stored as its rows.
+///
+/// ```
+/// use arse::gf2::{BitMatrix, BitVector};
+///
+/// // x + y = 0 and y + z = 0 leave only x = y = z
+/// let mut m = BitMatrix::zero(2, 3);
+/// m.set(0, 0, true);
+/// m.set(0, 1, true);
+/// m.set(1, 1, true);
+/// m.set(1, 2, true);
+/// assert_eq!(m.rank(), 2);
+/// let kernel = m.kernel();
+/// assert_eq!(kernel.len(), 1);
+/// assert_eq!(kernel[0].ones().collect::<Vec<_>>(), vec![0, 1, 2]);
+/// assert!(m.mul_vec(&kernel[0]).is_zero());
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BitMatrix {
+    cols: usize,
+    rows: Vec<BitVector>,
+}
+
+impl BitMatrix {
+    /// The all-zero matrix with `rows` rows and `cols` columns.
+    pub fn zero(rows: usize, cols: usize) -> Self {
+        BitMatrix {
+            cols,
+            rows: vec![BitVector::zero(cols); rows],
+        }
+    }
+
+    /// The `n` by `n` identity matrix.
+    pub fn identity(n: usize) -> Self {
+        let mut m = BitMatrix::zero(n, n);
+        for i in 0..n {
+            m.set(i, i, true);
+        }
+        m
+    }
+
+    /// The matrix with rows `rows`, which must all be `cols` long.
+    pub fn from_rows(cols: usize, rows: Vec<BitVector>) -> Self {
+        assert!(rows.iter().all(|r| r.len() == cols));
+        BitMatrix { cols, rows }
+    }
+
+    /// The matrix with columns `columns`, which must all be `rows` long.
+    pub fn from_columns(rows: usize, columns: &[BitVector]) -> Self {
+        BitMatrix::from_rows(rows, columns.to_vec()).transpose()
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> &[BitVector] {
+        &self.rows
+    }
+
+    pub fn row(&self, i: usize) -> &BitVector {
+        &self.rows[i]
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.rows[row].get(col)
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, bit: bool) {
+        self.rows[row].set(col, bit)
+    }
+
+    /// Add another row at the bottom.
+    pub fn push_row(&mut self, row: BitVector) {
+        assert_eq!(row.len(), self.cols);
+        self.rows.push(row);
+    }
+
+    pub fn transpose(&self) -> BitMatrix {
+        let mut t = BitMatrix::zero(self.cols, self.rows.len());
+        for (i, row) in self.rows.iter().enumerate() {
+            for j in row.ones() {
+                t.set(j, i, true);
+            }
+        }
+        t
+    }
+
+    /// The product `self * v`.
+    pub fn mul_vec(&self, v: &BitVector) -> BitVector {
+        assert_eq!(self.cols, v.len());
+        let mut product = BitVector::zero(self.rows.len());
+        for (i, row) in self.rows.iter().enumerate() {
+            product.set(i, row.dot(v));
+        }
+        product
+    }
+
+    /// The product `self * other`. Each row of the result is the sum of the rows of `other`
+    /// picked out by the same row of `self`.
+    pub fn mul(&self, other: &BitMatrix) -> BitMatrix {
+        assert_eq!(self.cols, other.rows.len());
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut sum = BitVector::zero(other.cols);
+                for j in row.ones() {
+                    sum ^= &other.rows[j];
+                }
+                sum
+            })
+            .collect();
+        BitMatrix::from_rows(other.cols, rows)
+    }
+
+    /// Put this matrix into reduced row echelon form by Gaussian elimination, returning the
+    /// pivot column of each nonzero row. Those rows come first, in order.
+    pub fn row_reduce(&mut self) -> Vec<usize> {
+        let mut pivots = Vec::new();
+        for col in 0..self.cols {
+            let r = pivots.len();
+            let found = match (r..self.rows.len()).find(|&i| self.rows[i].get(col)) {
+                Some(i) => i,
+                None => continue,
+            };
+            self.rows.swap(r, found);
+            let pivot = self.rows[r].clone();
+            for (i, row) in self.rows.iter_mut().enumerate() {
+                if i != r && row.get(col) {
+                    *row ^= &pivot;
+                }
+            }
+            pivots.push(col);
+            if pivots.len() == self.rows.len() {
+                break;
+            }
+        }
+        pivots
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().row_reduce().len()
+    }
+
+    /// A basis for the kernel, the vectors `v` with `self * v = 0`.
+    ///
+    /// In reduced row echelon form each free column gives one basis vector: set that variable,
+    /// and every pivot variable whose row mentions it.
+    pub fn kernel(&self) -> Vec<BitVector> {
+        let mut reduced = self.clone();
+        let pivots = reduced.row_reduce();
+        let mut is_pivot = vec![false; self.cols];
+        pivots.iter().for_each(|&p| is_pivot[p] = true);
+
+        (0..self.cols)
+            .filter(|&free| !is_pivot[free])
+            .map(|free| {
+                let mut v = BitVector::zero(self.cols);
+                v.set(free, true);
+                for (row, &p) in reduced.rows.iter().zip(&pivots) {
+                    if row.get(free) {
+                        v.set(p, true);
+                    }
+                }
+                v
+            })
+            .collect()
+    }
+}
+
+/// Matrix addition is XOR too.
+impl BitXorAssign<&BitMatrix> for BitMatrix {
+    fn bitxor_assign(&mut self, other: &BitMatrix) {
+        assert_eq!(self.cols, other.cols);
+        assert_eq!(self.rows.len(), other.rows.len());
+        for (a, b) in self.rows.iter_mut().zip(&other.rows) {
+            *a ^= b;
+        }
+    }
+}
@@ -0,0 +1,30 @@
+/// HMAC, for protocols which need a MAC with none of CBC-MAC's caveats.
+use sha2::{Digest, Sha256};
+
+/// Compute the HMAC-SHA256 of `message` under `key`.
+///
+/// ```
+/// use arse::encode::hex::ToHex;
+/// use arse::hmac::hmac_sha256;
+///
+/// assert_eq!(
+///     hmac_sha256(b"Jefe", b"what do ya want for nothing?").to_hex(),
+///     "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+/// );
+/// ```
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    const HMAC_BLOCK_SIZE: usize = 64;
+
+    let mut key = if key.len() > HMAC_BLOCK_SIZE {
+        Sha256::digest(key).to_vec()
+    } else {
+        key.to_vec()
+    };
+    key.resize(HMAC_BLOCK_SIZE, 0);
+
+    let mut inner = key.iter().map(|k| k ^ 0x36).collect::<Vec<u8>>();
+    inner.extend_from_slice(message);
+    let mut outer = key.iter().map(|k| k ^ 0x5c).collect::<Vec<u8>>();
+    outer.extend_from_slice(&Sha256::digest(&inner));
+    Sha256::digest(&outer).to_vec()
+}
@@ -0,0 +1,218 @@
+/// Pollard's kangaroo (lambda) algorithm for discrete logarithms known to lie in an interval.
+///
+/// A tame kangaroo starts at the top of the interval and makes a fixed number of pseudo-random
+/// jumps, each chosen by the element it lands on, and sets a trap where it stops. A wild kangaroo
+/// starts from the target and jumps by the same rule. If it ever lands where the tame kangaroo
+/// did, it follows the same path from then on and falls into the trap, and the distances the two
+/// travelled give away the logarithm. Expected work is about the square root of the interval's
+/// width, with memory for nothing but the two kangaroos.
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+use std::hash::Hash;
+
+/// A finite abelian group, written multiplicatively.
+pub trait AbelianGroup {
+    type Element: Clone + Eq + Hash;
+
+    /// The identity element.
+    fn identity(&self) -> Self::Element;
+
+    /// Combine two elements with the group operation.
+    fn combine(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+
+    /// Combine `a` with itself `k` times.
+    fn scale(&self, a: &Self::Element, k: &BigUint) -> Self::Element {
+        let mut result = self.identity();
+        for i in (0..k.bits()).rev() {
+            result = self.combine(&result, &result);
+            if !((k >> i) & BigUint::one()).is_zero() {
+                result = self.combine(&result, a);
+            }
+        }
+        result
+    }
+
+    /// Some bits derived deterministically from `a`, for choosing pseudo-random jumps.
+    fn fingerprint(&self, a: &Self::Element) -> u64;
+}
+
+/// The jump sizes available to the kangaroos, and how far the tame one goes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Jumps {
+    pub sizes: Vec<BigUint>,
+    /// The number of jumps the tame kangaroo makes before setting its trap.
+    pub trail: usize,
+}
+
+impl Jumps {
+    /// Jumps of `1, 2, 4, ..., 2^(k-1)`, with the tame kangaroo making four times the mean jump
+    /// size's worth of jumps.
+    pub fn powers_of_two(k: usize) -> Self {
+        let sizes = (0..k).map(|i| BigUint::one() << i).collect::<Vec<_>>();
+        let mut jumps = Jumps { sizes, trail: 0 };
+        jumps.trail = (jumps.mean() * 4u32).to_usize().unwrap_or(usize::MAX);
+        jumps
+    }
+
+    /// Powers of two whose mean is about half the square root of `width`, the usual choice for
+    /// an interval that wide.
+    pub fn for_interval(width: &BigUint) -> Self {
+        let target = width.sqrt() / 2u32;
+        let k = (1..)
+            .find(|&k| Jumps::powers_of_two(k).mean() >= target)
+            .expect("some power of two is big enough");
+        Jumps::powers_of_two(k)
+    }
+
+    /// The mean jump size.
+    pub fn mean(&self) -> BigUint {
+        self.sizes.iter().sum::<BigUint>() / self.sizes.len().max(1)
+    }
+}
+
+/// Find `x` in `[a, b]` with `g^x = y`, choosing each jump with `pick`, which maps an element to
+/// an index into `jumps.sizes` (reduced modulo the number of sizes). Returns `None` if the wild
+/// kangaroo overshoots the trap, which happens when `x` isn't in the interval (or, with small
+/// probability, even when it is).
+pub fn catch_with<G, F>(
+    group: &G,
+    g: &G::Element,
+    y: &G::Element,
+    a: &BigUint,
+    b: &BigUint,
+    jumps: &Jumps,
+    pick: F,
+) -> Option<BigUint>
+where
+    G: AbelianGroup,
+    F: Fn(&G::Element) -> usize,
+{
+    catch_any_with(group, g, std::slice::from_ref(y), a, b, jumps, pick).map(|(_, x)| x)
+}
+
+/// Like `catch_with`, but with a wild kangaroo for each of `ys` in turn, all falling into the
+/// same trap. Returns the index of the first one caught along with its logarithm.
+pub fn catch_any_with<G, F>(
+    group: &G,
+    g: &G::Element,
+    ys: &[G::Element],
+    a: &BigUint,
+    b: &BigUint,
+    jumps: &Jumps,
+    pick: F,
+) -> Option<(usize, BigUint)>
+where
+    G: AbelianGroup,
+    F: Fn(&G::Element) -> usize,
+{
+    let steps = jumps
+        .sizes
+        .iter()
+        .map(|size| group.scale(g, size))
+        .collect::<Vec<G::Element>>();
+    let jump = |at: &G::Element| pick(at) % jumps.sizes.len();
+
+    // the tame kangaroo runs from g^b and sets a trap where it stops
+    let mut tame_distance = BigUint::zero();
+    let mut tame = group.scale(g, b);
+    for _ in 0..jumps.trail {
+        let i = jump(&tame);
+        tame_distance += &jumps.sizes[i];
+        tame = group.combine(&tame, &steps[i]);
+    }
+
+    // each wild kangaroo runs from its y until it is caught, or has gone past the trap
+    let limit = b - a + &tame_distance;
+    for (index, y) in ys.iter().enumerate() {
+        let mut wild_distance = BigUint::zero();
+        let mut wild = y.clone();
+        while wild_distance <= limit {
+            if wild == tame {
+                return Some((index, b + &tame_distance - &wild_distance));
+            }
+            let i = jump(&wild);
+            wild_distance += &jumps.sizes[i];
+            wild = group.combine(&wild, &steps[i]);
+        }
+    }
+    None
+}
+
+/// Find `x` in `[a, b]` with `g^x = y`, with jumps sized for the interval and chosen by the
+/// group's fingerprint of each element.
+///
+/// A run can miss with small probability even when `x` is in the interval, so if the first one
+/// does, this tries again a few times with jumps chosen by different bits of the fingerprint.
+pub fn catch<G>(
+    group: &G,
+    g: &G::Element,
+    y: &G::Element,
+    a: &BigUint,
+    b: &BigUint,
+) -> Option<BigUint>
+where
+    G: AbelianGroup,
+{
+    catch_any(group, g, std::slice::from_ref(y), a, b).map(|(_, x)| x)
+}
+
+/// Find `x` in `[a, b]` with `g^x` equal to one of `ys`, returning which one along with `x`. The
+/// tame kangaroo's work is shared between them, so this is cheaper than trying each in turn.
+pub fn catch_any<G>(
+    group: &G,
+    g: &G::Element,
+    ys: &[G::Element],
+    a: &BigUint,
+    b: &BigUint,
+) -> Option<(usize, BigUint)>
+where
+    G: AbelianGroup,
+{
+    let jumps = Jumps::for_interval(&(b - a));
+    let k = jumps.sizes.len() as u64;
+    (0..4).find_map(|attempt| {
+        catch_any_with(group, g, ys, a, b, &jumps, |e| {
+            (group.fingerprint(e).rotate_right(16 * attempt) % k) as usize
+        })
+    })
+}
+
+/// Find `x` with `g^x = y`, where `g` has order `order`, given that `x = n mod r`.
+///
+/// Writing `x = n + m * r` gives `y * g^-n = (g^r)^m`, where `m` is at most `order / r`, so the
+/// rest of `x` is a discrete log in a much smaller interval.
+pub fn catch_with_residue<G>(
+    group: &G,
+    g: &G::Element,
+    y: &G::Element,
+    n: &BigUint,
+    r: &BigUint,
+    order: &BigUint,
+) -> Option<BigUint>
+where
+    G: AbelianGroup,
+{
+    catch_with_residues(group, g, y, std::slice::from_ref(n), r, order)
+}
+
+/// Find `x` with `g^x = y`, where `g` has order `order`, given that `x` is congruent mod `r` to
+/// one of `ns`.
+pub fn catch_with_residues<G>(
+    group: &G,
+    g: &G::Element,
+    y: &G::Element,
+    ns: &[BigUint],
+    r: &BigUint,
+    order: &BigUint,
+) -> Option<BigUint>
+where
+    G: AbelianGroup,
+{
+    let shifted = ns
+        .iter()
+        .map(|n| group.combine(y, &group.scale(g, &(order - n % order))))
+        .collect::<Vec<_>>();
+    let base = group.scale(g, r);
+    let (i, m) = catch_any(group, &base, &shifted, &BigUint::zero(), &(order / r))?;
+    Some((&ns[i] + m * r) % order)
+}
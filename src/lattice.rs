@@ -0,0 +1,111 @@
+/// Lattices over the rationals, and Lenstra-Lenstra-Lovász basis reduction.
+///
+/// A lattice is every integer combination of its basis vectors. LLL turns a basis into another
+/// basis for the same lattice whose vectors are short and nearly orthogonal, which makes it good
+/// for finding small solutions hidden in systems of linear equations.
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+/// A vector with exact rational coordinates.
+pub type Vector = Vec<BigRational>;
+
+/// The usual choice of the LLL parameter `delta`, 99/100.
+pub fn default_delta() -> BigRational {
+    BigRational::new(BigInt::from(99), BigInt::from(100))
+}
+
+/// The inner product of `a` and `b`.
+pub fn dot(a: &[BigRational], b: &[BigRational]) -> BigRational {
+    a.iter()
+        .zip(b)
+        .fold(BigRational::zero(), |acc, (x, y)| acc + x * y)
+}
+
+/// Subtract `k * b` from `a`.
+fn sub_scaled(a: &mut Vector, b: &[BigRational], k: &BigRational) {
+    for (x, y) in a.iter_mut().zip(b) {
+        *x -= k * y;
+    }
+}
+
+/// Orthogonalize `basis` by the Gram-Schmidt process, returning the orthogonal vectors `b*` and
+/// the coefficients `mu[i][j] = <b_i, b*_j> / <b*_j, b*_j>` for `j < i`.
+pub fn gram_schmidt(basis: &[Vector]) -> (Vec<Vector>, Vec<Vec<BigRational>>) {
+    let n = basis.len();
+    let mut orthogonal: Vec<Vector> = Vec::with_capacity(n);
+    let mut mu = vec![vec![BigRational::zero(); n]; n];
+    for (i, b) in basis.iter().enumerate() {
+        let mut v = b.clone();
+        for (j, o) in orthogonal.iter().enumerate() {
+            let norm = dot(o, o);
+            if !norm.is_zero() {
+                mu[i][j] = dot(b, o) / norm;
+                sub_scaled(&mut v, o, &mu[i][j]);
+            }
+        }
+        orthogonal.push(v);
+    }
+    (orthogonal, mu)
+}
+
+/// Reduce `basis`, whose vectors must be linearly independent, with the LLL algorithm and
+/// parameter `delta` in `(1/4, 1)`.
+///
+/// The Gram-Schmidt data is computed once up front and then updated in place as vectors are
+/// reduced and swapped, rather than recomputed from scratch each time.
+pub fn lll(basis: &[Vector], delta: &BigRational) -> Vec<Vector> {
+    let n = basis.len();
+    let mut b = basis.to_vec();
+    if n < 2 {
+        return b;
+    }
+    let (orthogonal, mut mu) = gram_schmidt(&b);
+    let mut norms = orthogonal.iter().map(|o| dot(o, o)).collect::<Vec<_>>();
+    let half = BigRational::new(BigInt::one(), BigInt::from(2));
+
+    // make |mu[k][l]| at most 1/2 by subtracting a multiple of b_l from b_k
+    let reduce = |b: &mut Vec<Vector>, mu: &mut Vec<Vec<BigRational>>, k: usize, l: usize| {
+        if mu[k][l].abs() <= half {
+            return;
+        }
+        let q = mu[k][l].round();
+        let b_l = b[l].clone();
+        sub_scaled(&mut b[k], &b_l, &q);
+        mu[k][l] -= &q;
+        let (head, tail) = mu.split_at_mut(k);
+        for (x, y) in tail[0][..l].iter_mut().zip(&head[l][..l]) {
+            *x -= &q * y;
+        }
+    };
+
+    let mut k = 1;
+    while k < n {
+        reduce(&mut b, &mut mu, k, k - 1);
+
+        let m = mu[k][k - 1].clone();
+        if norms[k] < (delta - &m * &m) * &norms[k - 1] {
+            // the Lovász condition fails, so swap b_k and b_(k-1) and fix up the Gram-Schmidt
+            // data for the two of them and everything after
+            b.swap(k, k - 1);
+            let (head, tail) = mu.split_at_mut(k);
+            head[k - 1][..k - 1].swap_with_slice(&mut tail[0][..k - 1]);
+            let norm = &norms[k] + &m * &m * &norms[k - 1];
+            mu[k][k - 1] = &m * &norms[k - 1] / &norm;
+            norms[k] = &norms[k - 1] * &norms[k] / &norm;
+            norms[k - 1] = norm;
+            for i in k + 1..n {
+                let t = mu[i][k].clone();
+                mu[i][k] = &mu[i][k - 1] - &m * &t;
+                mu[i][k - 1] = t + &mu[k][k - 1] * &mu[i][k];
+            }
+            k = (k - 1).max(1);
+        } else {
+            for l in (0..k - 1).rev() {
+                reduce(&mut b, &mut mu, k, l);
+            }
+            k += 1;
+        }
+    }
+    b
+}
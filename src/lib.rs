@@ -1,29 +1,84 @@
 /// Solutions to the [Cryptopals Challenges](https://cryptopals.com)
 ///
 /// Written whilst leading the *A*spiring *R*ustacean *S*ocial *E*ducation group within LinkedIn
+pub mod compression;
+pub mod dh;
+pub mod dsa;
+pub mod dsks;
+pub mod ec;
+pub mod ecdsa;
 pub mod encode;
 pub mod encrypt;
+pub mod gcm;
+pub mod gf128;
+pub mod gf2;
+pub mod hmac;
+pub mod kangaroo;
+pub mod lattice;
+pub mod mac;
+pub mod math;
+pub mod md;
+pub mod md4;
+pub mod rc4_bias;
+pub mod rsa;
 pub mod stat;
 pub mod transform;
 
 pub mod xor_cipher {
-    use super::stat::{Histogram, HistogramError};
+    use super::stat::{find_best_in, Histogram, HistogramError, NgramModel};
     use super::transform::TryFixedXor;
-    use std::f64;
+    use std::cmp::Ordering;
+    use std::{f64, str};
 
-    /// Bytewise XOR `ciphertext` with `test_byte`, and then measure the chi-square goodness of fit
-    /// of the resulting output with `language`.
-    pub fn score_byte_decode(
-        test_byte: u8,
-        ciphertext: &[u8],
-        language: &Histogram<char>,
-    ) -> Result<f64, HistogramError> {
-        let bytes = ciphertext
-            .try_fixed_xor(vec![test_byte; ciphertext.len()].as_slice())
-            .unwrap();
-        let b_len = bytes.len();
+    /// A model of what plaintext looks like, which can score a candidate decryption. Lower scores
+    /// are more plausible.
+    pub trait PlaintextScorer {
+        fn score(&self, plaintext: &[u8]) -> Result<f64, HistogramError>;
+    }
+
+    /// Letter frequencies score by chi-squared, weighted by the fraction of the plaintext that
+    /// isn't letters.
+    impl PlaintextScorer for Histogram<char> {
+        fn score(&self, plaintext: &[u8]) -> Result<f64, HistogramError> {
+            match decode_letters(plaintext)? {
+                Decoded::Unprintable => Ok(f64::MAX),
+                Decoded::Letters(letters, pct_non_alpha) => {
+                    Ok(letters.chisq(self)? * pct_non_alpha)
+                }
+            }
+        }
+    }
+
+    /// Byte frequencies score by negative log-likelihood per byte. Every byte counts, spaces and
+    /// punctuation included, and the plaintext needn't be UTF-8, so any candidate gets a score.
+    impl PlaintextScorer for Histogram<u8> {
+        fn score(&self, plaintext: &[u8]) -> Result<f64, HistogramError> {
+            Ok(self.log_likelihood(plaintext).map_or(f64::MAX, |l| -l))
+        }
+    }
+
+    /// N-gram models score by negative log-likelihood per n-gram.
+    impl PlaintextScorer for NgramModel {
+        fn score(&self, plaintext: &[u8]) -> Result<f64, HistogramError> {
+            self.log_likelihood(plaintext)
+                .map(|l| -l)
+                .ok_or(HistogramError::HistogramMismatch)
+        }
+    }
+
+    /// What's left of a candidate decode for comparing with letter frequencies.
+    enum Decoded {
+        /// The output contains a null byte, so it can't be text.
+        Unprintable,
+        /// The letter frequencies of the output, and the fraction of it that isn't letters.
+        Letters(Histogram<char>, f64),
+    }
 
-        match String::from_utf8(bytes) {
+    /// Pull out the letters of `plaintext`.
+    fn decode_letters(plaintext: &[u8]) -> Result<Decoded, HistogramError> {
+        let b_len = plaintext.len();
+
+        match str::from_utf8(plaintext) {
             Ok(s) => {
                 if s.len() != b_len {
                     return Err(HistogramError::HistogramMismatch);
@@ -32,29 +87,109 @@ pub mod xor_cipher {
                 // if the resulting string contains a null byte, it's not printable and can be
                 // discarded immediately.
                 if s.contains(|c| c == '\0') {
-                    return Ok(f64::MAX);
+                    return Ok(Decoded::Unprintable);
                 }
 
-                let s = s
+                let letters = s
                     .to_lowercase()
                     .chars()
                     .filter(|&c| c.is_alphabetic())
                     .collect::<String>();
-                if s.len() == 0 {
+                if letters.is_empty() {
                     return Err(HistogramError::HistogramMismatch);
                 }
 
-                let mut byte_distr: Histogram<char> = s.chars().into();
-                byte_distr.normalize();
-                match byte_distr.chisq(language) {
-                    Ok(raw_score) => {
-                        let pct_non_alpha = (b_len - s.len()) as f64 / b_len as f64;
-                        Ok(raw_score * pct_non_alpha)
-                    }
-                    Err(e) => Err(e),
-                }
+                let pct_non_alpha = (b_len - letters.len()) as f64 / b_len as f64;
+                Ok(Decoded::Letters(
+                    Histogram::letters(&letters),
+                    pct_non_alpha,
+                ))
             }
             Err(_) => Err(HistogramError::HistogramMismatch),
         }
     }
+
+    /// Bytewise XOR `ciphertext` with `test_byte`.
+    fn xor_byte(test_byte: u8, ciphertext: &[u8]) -> Vec<u8> {
+        ciphertext
+            .try_fixed_xor(vec![test_byte; ciphertext.len()].as_slice())
+            .unwrap()
+    }
+
+    /// Bytewise XOR `ciphertext` with `test_byte`, and then score how much the resulting output
+    /// looks like `language`: the chi-square goodness of fit for letter frequencies, or the
+    /// log-likelihood for an n-gram model.
+    pub fn score_byte_decode<S: PlaintextScorer + ?Sized>(
+        test_byte: u8,
+        ciphertext: &[u8],
+        language: &S,
+    ) -> Result<f64, HistogramError> {
+        language.score(&xor_byte(test_byte, ciphertext))
+    }
+
+    /// Score every possible key byte for `ciphertext` under `language`, best first. Keys whose
+    /// output can't be scored at all rank last, with the worst possible score.
+    ///
+    /// ```
+    /// use arse::stat::Histogram;
+    /// use arse::xor_cipher::rank_byte_decodes;
+    ///
+    /// let ciphertext = b"Short, but: sweet!".iter().map(|b| b ^ 0xc3).collect::<Vec<_>>();
+    /// let ranking = rank_byte_decodes(&ciphertext, &Histogram::english_bytes());
+    /// assert_eq!(ranking.len(), 256);
+    /// assert_eq!(ranking[0].0, 0xc3);
+    /// ```
+    pub fn rank_byte_decodes<S: PlaintextScorer + ?Sized>(
+        ciphertext: &[u8],
+        language: &S,
+    ) -> Vec<(u8, f64)> {
+        let mut ranking = (0..=255u8)
+            .map(|b| {
+                let score = score_byte_decode(b, ciphertext, language).unwrap_or(f64::MAX);
+                (b, score)
+            })
+            .collect::<Vec<_>>();
+        ranking.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        ranking
+    }
+
+    /// Like `score_byte_decode`, but against each of `languages`, returning the best score and
+    /// the name of the language that gave it. Languages whose models lack some letter of the
+    /// output are skipped, and an output that can't be text is an error rather than a worst
+    /// possible score, since it isn't in any language.
+    ///
+    /// ```
+    /// use arse::stat::{find_best_in, Histogram};
+    /// use arse::xor_cipher::score_byte_decode_any;
+    ///
+    /// let languages = Histogram::languages();
+    /// let text = "Le cœur a ses raisons que la raison ne connaît point, et on le sait en mille choses.";
+    /// let ciphertext = text.bytes().map(|b| b ^ 0x2a).collect::<Vec<_>>();
+    /// let key = find_best_in(
+    ///     0..=255u8,
+    ///     |&b| score_byte_decode_any(b, &ciphertext, &languages).ok().map(|(s, _)| s),
+    ///     f64::lt,
+    /// );
+    /// assert_eq!(key, Some(0x2a));
+    /// let (_, language) = score_byte_decode_any(0x2a, &ciphertext, &languages).unwrap();
+    /// assert_eq!(language, "french");
+    /// ```
+    pub fn score_byte_decode_any<'a>(
+        test_byte: u8,
+        ciphertext: &[u8],
+        languages: &'a [(&'a str, Histogram<char>)],
+    ) -> Result<(f64, &'a str), HistogramError> {
+        match decode_letters(&xor_byte(test_byte, ciphertext))? {
+            Decoded::Unprintable => Err(HistogramError::HistogramMismatch),
+            Decoded::Letters(letters, pct_non_alpha) => find_best_in(
+                languages.iter().filter_map(|(name, model)| {
+                    let score = letters.chisq(model).ok()?;
+                    Some((score * pct_non_alpha, *name))
+                }),
+                |&(score, _)| Some(score),
+                f64::lt,
+            )
+            .ok_or(HistogramError::BucketNotFound),
+        }
+    }
 }
@@ -0,0 +1,111 @@
+/// CBC-MAC, and forgeries against it.
+///
+/// CBC-MAC is the last block of a CBC encryption of the (padded) message. That makes it a fine
+/// MAC when the IV is fixed and messages are of a fixed length, and a poor one otherwise. It is
+/// no kind of hash function at all: anyone who knows the key can find collisions at will.
+use crate::encrypt::{aes128_decrypt_block, cbc_encrypt_with_iv, EncryptError, BLOCK_SIZE};
+use crate::transform::{pkcs7_pad_blocks, XorWith};
+
+pub mod transfer;
+
+/// Compute the CBC-MAC of `message` under `key`, starting from `iv`. The message is PKCS#7 padded
+/// before being encrypted.
+pub fn cbc_mac(key: &[u8], iv: &[u8], message: &[u8]) -> Result<Vec<u8>, EncryptError> {
+    let padded = pkcs7_pad_blocks(message.to_vec(), BLOCK_SIZE)
+        .expect("the block size is always a valid padding length");
+    let ciphertext = cbc_encrypt_with_iv(key, iv, &padded)?;
+    Ok(ciphertext[ciphertext.len() - BLOCK_SIZE..].to_vec())
+}
+
+/// Compute the CBC-MAC of `message` under `key` with a fixed, all-zero IV.
+pub fn cbc_mac_fixed_iv(key: &[u8], message: &[u8]) -> Result<Vec<u8>, EncryptError> {
+    cbc_mac(key, &[0u8; BLOCK_SIZE], message)
+}
+
+/// Given a message signed with an attacker-controlled IV, find the IV under which `forged` has
+/// the same MAC.
+///
+/// Only the first block of the message passes through the IV, so `forged` must be the same length
+/// as `message` and differ from it only within the first block. Returns `None` otherwise.
+pub fn forge_iv(message: &[u8], iv: &[u8], forged: &[u8]) -> Option<Vec<u8>> {
+    if message.len() != forged.len()
+        || iv.len() != BLOCK_SIZE
+        || message.get(BLOCK_SIZE..) != forged.get(BLOCK_SIZE..)
+    {
+        return None;
+    }
+
+    let first = message.len().min(BLOCK_SIZE);
+    let mut delta = (&message[..first]).xor_with(&forged[..first]);
+    // a message shorter than a block has its padding in the first block too, which is unchanged
+    delta.resize(BLOCK_SIZE, 0);
+    Some(iv.xor_with(&delta))
+}
+
+/// Glue `extension` onto `message` such that the result has the same fixed-IV CBC-MAC as
+/// `extension` alone.
+///
+/// `mac` is the MAC of `message`. Because the CBC state after the padded `message` is exactly
+/// `mac`, XORing it into the first block of `extension` resets the chain to where a zero IV would
+/// have left it. The first block of `extension` is garbled in the process.
+///
+/// Returns `None` if `extension` is shorter than a block, since its padding would then be garbled
+/// as well.
+pub fn extend(message: &[u8], mac: &[u8], extension: &[u8]) -> Option<Vec<u8>> {
+    if extension.len() < BLOCK_SIZE || mac.len() != BLOCK_SIZE {
+        return None;
+    }
+
+    let mut forged = pkcs7_pad_blocks(message.to_vec(), BLOCK_SIZE)
+        .expect("the block size is always a valid padding length");
+    forged.extend((&extension[..BLOCK_SIZE]).xor_with(mac));
+    forged.extend_from_slice(&extension[BLOCK_SIZE..]);
+    Some(forged)
+}
+
+/// Compute the block which, appended to the block-aligned `prefix`, gives the whole message the
+/// fixed-IV CBC-MAC `target` under `key`.
+///
+/// Anyone holding the key can run CBC backwards. The appended block is followed by a full block
+/// of padding when the MAC is computed, so the state needed before that padding block is
+/// `D(target) ^ padding`, and the glue block is whatever takes the chain from the end of
+/// `prefix` to that state.
+pub fn glue_block(key: &[u8], prefix: &[u8], target: &[u8]) -> Result<Vec<u8>, EncryptError> {
+    if prefix.len() % BLOCK_SIZE != 0 {
+        return Err(EncryptError::BlockAlignment);
+    }
+    if target.len() != BLOCK_SIZE {
+        return Err(EncryptError::BlockAlignment);
+    }
+
+    let state = match cbc_encrypt_with_iv(key, &[0u8; BLOCK_SIZE], prefix)? {
+        ref c if c.is_empty() => vec![0u8; BLOCK_SIZE],
+        c => c[c.len() - BLOCK_SIZE..].to_vec(),
+    };
+
+    let mut needed = target.to_vec();
+    aes128_decrypt_block(key, &mut needed)?;
+    let mut needed = needed.xor_with([BLOCK_SIZE as u8; BLOCK_SIZE]);
+    aes128_decrypt_block(key, &mut needed)?;
+    Ok(needed.xor_with(&state))
+}
+
+/// Produce a JavaScript snippet which runs `code` and whose CBC-MAC "hash" under `key` is
+/// `target`.
+///
+/// The code is followed by a `//` comment, padded out with spaces to a block boundary, and then
+/// the glue block. The glue block is random-looking garbage, but as long as it contains no line
+/// terminator it stays inside the comment; if it does contain one, another block of spaces is
+/// added and the glue recomputed.
+pub fn forge_javascript(key: &[u8], code: &[u8], target: &[u8]) -> Result<Vec<u8>, EncryptError> {
+    let mut prefix = code.to_vec();
+    prefix.extend_from_slice(b"//");
+    loop {
+        prefix.resize((prefix.len() / BLOCK_SIZE + 1) * BLOCK_SIZE, b' ');
+        let glue = glue_block(key, &prefix, target)?;
+        if !glue.iter().any(|&b| b == b'\n' || b == b'\r') {
+            prefix.extend(glue);
+            return Ok(prefix);
+        }
+    }
+}
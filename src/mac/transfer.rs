@@ -0,0 +1,217 @@
+/// A simulated money-transfer API whose requests are authenticated with CBC-MAC.
+///
+/// Two API versions are supported. The first signs a single transfer with a client-chosen IV:
+///
+/// ```text
+/// from=#{from_id}&to=#{to_id}&amount=#{amount} || IV || MAC
+/// ```
+///
+/// The second uses a fixed IV, and allows several transfers per request:
+///
+/// ```text
+/// from=#{from_id}&tx_list=#{to:amount(;to:amount)*} || MAC
+/// ```
+use super::{cbc_mac, cbc_mac_fixed_iv, extend};
+use crate::encrypt::BLOCK_SIZE;
+use rand::{thread_rng, RngCore};
+
+/// A single movement of money between two accounts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Transfer {
+    pub from: u32,
+    pub to: u32,
+    pub amount: u64,
+}
+
+/// Reasons the server may reject a request.
+#[derive(Debug, Eq, PartialEq)]
+pub enum ApiError {
+    /// The request is too short to contain its IV and MAC.
+    TooShort,
+    /// The MAC does not match the message.
+    BadMac,
+    /// The message could not be understood.
+    Malformed,
+}
+
+/// A web client, logged in as one account, which signs requests on that account's behalf.
+pub struct Client {
+    key: Vec<u8>,
+    account: u32,
+}
+
+impl Client {
+    /// Create a client for `account`, sharing `key` with the server.
+    pub fn new(key: &[u8], account: u32) -> Self {
+        Client {
+            key: key.to_vec(),
+            account,
+        }
+    }
+
+    /// The account this client is logged in as.
+    pub fn account(&self) -> u32 {
+        self.account
+    }
+
+    /// Produce a signed version 1 request transferring `amount` to `to`, under a random IV.
+    pub fn transfer(&self, to: u32, amount: u64) -> Vec<u8> {
+        let mut iv = vec![0u8; BLOCK_SIZE];
+        thread_rng().fill_bytes(&mut iv);
+
+        let mut request = format!("from={}&to={}&amount={}", self.account, to, amount).into_bytes();
+        let mac = cbc_mac(&self.key, &iv, &request).expect("client key is valid");
+        request.extend(iv);
+        request.extend(mac);
+        request
+    }
+
+    /// Produce a signed version 2 request making each of the `(to, amount)` transfers.
+    pub fn transfer_list(&self, transfers: &[(u32, u64)]) -> Vec<u8> {
+        let tx_list = transfers
+            .iter()
+            .map(|(to, amount)| format!("{}:{}", to, amount))
+            .collect::<Vec<String>>()
+            .join(";");
+
+        let mut request = format!("from={}&tx_list={}", self.account, tx_list).into_bytes();
+        let mac = cbc_mac_fixed_iv(&self.key, &request).expect("client key is valid");
+        request.extend(mac);
+        request
+    }
+}
+
+/// The API server, which verifies requests and reports the transfers it would make.
+pub struct Server {
+    key: Vec<u8>,
+}
+
+impl Server {
+    /// Create a server using `key`.
+    pub fn new(key: &[u8]) -> Self {
+        Server { key: key.to_vec() }
+    }
+
+    /// Handle a version 1 request: `message || IV || MAC`.
+    pub fn handle_v1(&self, request: &[u8]) -> Result<Transfer, ApiError> {
+        if request.len() < 2 * BLOCK_SIZE {
+            return Err(ApiError::TooShort);
+        }
+        let (message, rest) = request.split_at(request.len() - 2 * BLOCK_SIZE);
+        let (iv, mac) = rest.split_at(BLOCK_SIZE);
+
+        if cbc_mac(&self.key, iv, message).map_err(|_| ApiError::BadMac)? != mac {
+            return Err(ApiError::BadMac);
+        }
+
+        let fields = parse_fields(message);
+        Ok(Transfer {
+            from: field(&fields, "from")?,
+            to: field(&fields, "to")?,
+            amount: field(&fields, "amount")?,
+        })
+    }
+
+    /// Handle a version 2 request: `message || MAC`.
+    ///
+    /// Like many real parsers, this one is forgiving: entries in the transaction list which can't
+    /// be understood are skipped rather than failing the whole request.
+    pub fn handle_v2(&self, request: &[u8]) -> Result<Vec<Transfer>, ApiError> {
+        if request.len() < BLOCK_SIZE {
+            return Err(ApiError::TooShort);
+        }
+        let (message, mac) = request.split_at(request.len() - BLOCK_SIZE);
+
+        if cbc_mac_fixed_iv(&self.key, message).map_err(|_| ApiError::BadMac)? != mac {
+            return Err(ApiError::BadMac);
+        }
+
+        parse_transfer_list(message)
+    }
+}
+
+/// Parse the transfers in a version 2 message, skipping entries which can't be understood just as
+/// `Server::handle_v2` does.
+fn parse_transfer_list(message: &[u8]) -> Result<Vec<Transfer>, ApiError> {
+    let fields = parse_fields(message);
+    let from = field(&fields, "from")?;
+    let tx_list = fields
+        .iter()
+        .find(|(k, _)| k == b"tx_list")
+        .map(|(_, v)| v)
+        .ok_or(ApiError::Malformed)?;
+
+    Ok(tx_list
+        .split(|&b| b == b';')
+        .filter_map(|tx| {
+            let mut parts = tx.splitn(2, |&b| b == b':');
+            let to = parse_number(parts.next()?).ok()?;
+            let amount = parse_number(parts.next()?).ok()?;
+            Some(Transfer { from, to, amount })
+        })
+        .collect())
+}
+
+fn parse_fields(message: &[u8]) -> Vec<(&[u8], &[u8])> {
+    message
+        .split(|&b| b == b'&')
+        .filter_map(|pair| {
+            let mut kv = pair.splitn(2, |&b| b == b'=');
+            Some((kv.next()?, kv.next()?))
+        })
+        .collect()
+}
+
+fn field<T: std::str::FromStr>(fields: &[(&[u8], &[u8])], name: &str) -> Result<T, ApiError> {
+    fields
+        .iter()
+        .find(|(k, _)| *k == name.as_bytes())
+        .ok_or(ApiError::Malformed)
+        .and_then(|(_, v)| parse_number(v))
+}
+
+fn parse_number<T: std::str::FromStr>(value: &[u8]) -> Result<T, ApiError> {
+    std::str::from_utf8(value)
+        .map_err(|_| ApiError::Malformed)?
+        .parse()
+        .map_err(|_| ApiError::Malformed)
+}
+
+/// Forge a version 2 request which makes every transfer in the `captured` request, and then pays
+/// `amount` into the attacker's account.
+///
+/// The attacker's `client` signs a version 2 request of its own, and `mac::extend` glues it onto
+/// the captured one. The captured padding runs into the victim's last transfer, so the attacker's
+/// request makes that transfer again before paying the attacker, and everything it lists is
+/// parsed as coming from the victim.
+///
+/// Gluing garbles the first block of the attacker's request, `from=N&tx_list=...`, by XORing it
+/// with the captured MAC. The garbled block lands in the middle of the victim's transaction list;
+/// the attacker's request starts with a throwaway transfer to soak up whatever of its list is in
+/// that block, so the rest starts cleanly after a `;`. Nothing the attacker can sign changes the
+/// block once the account number has two digits, so if it garbles into a `&`, cutting the list
+/// short, this returns `None`. That happens to about one captured request in sixteen; the next
+/// one the victim sends will most likely do.
+pub fn append_transfer(captured: &[u8], client: &Client, amount: u64) -> Option<Vec<u8>> {
+    if captured.len() < BLOCK_SIZE {
+        return None;
+    }
+    let (message, mac) = captured.split_at(captured.len() - BLOCK_SIZE);
+    let mut wanted = parse_transfer_list(message).ok()?;
+    let last = wanted.last()?.clone();
+    wanted.push(Transfer {
+        from: last.from,
+        to: client.account(),
+        amount,
+    });
+
+    let signed =
+        client.transfer_list(&[(0, 0), (last.to, last.amount), (client.account(), amount)]);
+    let (extension, ext_mac) = signed.split_at(signed.len() - BLOCK_SIZE);
+    let mut forged = extend(message, mac, extension)?;
+    if parse_transfer_list(&forged).ok()? != wanted {
+        return None;
+    }
+    forged.extend_from_slice(ext_mac);
+    Some(forged)
+}
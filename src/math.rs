@@ -0,0 +1,276 @@
+/// Number-theoretic helpers for the public-key challenges.
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::{thread_rng, RngCore};
+
+/// Run the extended Euclidean algorithm on `a` and `b`, returning `(g, x, y)` such that
+/// `a*x + b*y = g = gcd(a, b)`.
+pub fn egcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
+    let (mut old_r, mut r) = (a.clone(), b.clone());
+    let (mut old_s, mut s) = (BigInt::one(), BigInt::zero());
+    let (mut old_t, mut t) = (BigInt::zero(), BigInt::one());
+
+    while !r.is_zero() {
+        let q = &old_r / &r;
+        let next_r = &old_r - &q * &r;
+        old_r = std::mem::replace(&mut r, next_r);
+        let next_s = &old_s - &q * &s;
+        old_s = std::mem::replace(&mut s, next_s);
+        let next_t = &old_t - &q * &t;
+        old_t = std::mem::replace(&mut t, next_t);
+    }
+
+    (old_r, old_s, old_t)
+}
+
+/// Find the multiplicative inverse of `a` modulo `m`, if one exists.
+///
+/// ```
+/// use arse::math::invmod;
+/// use num_bigint::BigUint;
+///
+/// let a = BigUint::from(17u32);
+/// let m = BigUint::from(3120u32);
+/// assert_eq!(invmod(&a, &m), Some(BigUint::from(2753u32)));
+/// assert_eq!(invmod(&BigUint::from(2u32), &BigUint::from(4u32)), None);
+/// ```
+pub fn invmod(a: &BigUint, m: &BigUint) -> Option<BigUint> {
+    let a = BigInt::from_biguint(Sign::Plus, a.clone());
+    let m = BigInt::from_biguint(Sign::Plus, m.clone());
+    let (g, x, _) = egcd(&a, &m);
+    if !g.is_one() {
+        return None;
+    }
+    x.mod_floor(&m).to_biguint()
+}
+
+/// Generate a uniformly random value in `[0, bound)`.
+pub fn rand_below(bound: &BigUint) -> BigUint {
+    let bits = bound.bits();
+    let mut buf = vec![0u8; bits.div_ceil(8)];
+    let excess = buf.len() * 8 - bits;
+    loop {
+        thread_rng().fill_bytes(&mut buf);
+        if let Some(top) = buf.first_mut() {
+            *top &= 0xFF >> excess;
+        }
+        let candidate = BigUint::from_bytes_be(&buf);
+        if &candidate < bound {
+            return candidate;
+        }
+    }
+}
+
+/// Generate a uniformly random value in `[low, high)`.
+pub fn rand_range(low: &BigUint, high: &BigUint) -> BigUint {
+    low + rand_below(&(high - low))
+}
+
+const SMALL_PRIMES: &[u32] = &[
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// Test `n` for primality with `rounds` rounds of Miller-Rabin.
+///
+/// ```
+/// use arse::math::is_probable_prime;
+/// use num_bigint::BigUint;
+///
+/// assert!(is_probable_prime(&BigUint::from(7919u32), 20));
+/// assert!(!is_probable_prime(&BigUint::from(7917u32), 20));
+/// ```
+pub fn is_probable_prime(n: &BigUint, rounds: usize) -> bool {
+    let one = BigUint::one();
+    let two = BigUint::from(2u32);
+    if n < &two {
+        return false;
+    }
+    for &p in SMALL_PRIMES {
+        let p = BigUint::from(p);
+        if n == &p {
+            return true;
+        }
+        if (n % &p).is_zero() {
+            return false;
+        }
+    }
+
+    let n_minus_one = n - &one;
+    let mut d = n_minus_one.clone();
+    let mut s = 0;
+    while d.is_even() {
+        d >>= 1;
+        s += 1;
+    }
+
+    'witness: for _ in 0..rounds {
+        let a = rand_range(&two, &n_minus_one);
+        let mut x = a.modpow(&d, n);
+        if x == one || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Generate a random prime of exactly `bits` bits.
+pub fn gen_prime(bits: usize) -> BigUint {
+    let low = BigUint::one() << (bits - 1);
+    let high = BigUint::one() << bits;
+    loop {
+        let candidate = rand_range(&low, &high) | BigUint::one();
+        if is_probable_prime(&candidate, 32) {
+            return candidate;
+        }
+    }
+}
+
+/// Find the distinct prime factors of `n` below `bound` by trial division.
+///
+/// ```
+/// use arse::math::small_factors;
+/// use num_bigint::BigUint;
+///
+/// let n = BigUint::from(2u64 * 2 * 3 * 7963 * 1_000_003);
+/// assert_eq!(small_factors(&n, 1 << 16), vec![2, 3, 7963]);
+/// ```
+pub fn small_factors(n: &BigUint, bound: u32) -> Vec<u32> {
+    let mut n = n.clone();
+    let mut factors = Vec::new();
+    for d in 2..bound {
+        if n.is_one() {
+            break;
+        }
+        let divisor = BigUint::from(d);
+        if (&n % &divisor).is_zero() {
+            factors.push(d);
+            while (&n % &divisor).is_zero() {
+                n /= &divisor;
+            }
+        }
+    }
+    factors
+}
+
+/// Combine `x = a_i mod m_i` for pairwise coprime moduli into `(x mod M, M)` with the Chinese
+/// remainder theorem, where `M` is the product of the moduli. Returns `None` if the moduli
+/// aren't coprime.
+///
+/// ```
+/// use arse::math::crt;
+/// use num_bigint::BigUint;
+///
+/// let residues = [(2u32, 3u32), (3, 5), (2, 7)]
+///     .iter()
+///     .map(|&(a, m)| (BigUint::from(a), BigUint::from(m)))
+///     .collect::<Vec<_>>();
+/// assert_eq!(crt(&residues), Some((BigUint::from(23u32), BigUint::from(105u32))));
+/// ```
+pub fn crt(residues: &[(BigUint, BigUint)]) -> Option<(BigUint, BigUint)> {
+    residues
+        .iter()
+        .try_fold((BigUint::zero(), BigUint::one()), |(x, m), (a, n)| {
+            // find x' = x mod m and x' = a mod n
+            let t = ((a + n - &x % n) % n * invmod(&(&m % n), n)?) % n;
+            Some((x + &m * t, m * n))
+        })
+}
+
+/// Find a square root of `n` modulo the odd prime `p` with the Tonelli-Shanks algorithm, or
+/// `None` if `n` isn't a square. The other root is `p` minus this one.
+///
+/// ```
+/// use arse::math::sqrt_mod;
+/// use num_bigint::BigUint;
+///
+/// let p = BigUint::from(113u32);
+/// let root = sqrt_mod(&BigUint::from(2u32), &p).unwrap();
+/// assert_eq!(&root * &root % &p, BigUint::from(2u32));
+/// assert_eq!(sqrt_mod(&BigUint::from(3u32), &p), None);
+/// ```
+pub fn sqrt_mod(n: &BigUint, p: &BigUint) -> Option<BigUint> {
+    let n = n % p;
+    if n.is_zero() {
+        return Some(n);
+    }
+    let one = BigUint::one();
+    let p_minus_one = p - 1u32;
+    let half = &p_minus_one >> 1;
+    if n.modpow(&half, p) != one {
+        return None;
+    }
+
+    // write p - 1 = q * 2^s with q odd, and find a non-residue z
+    let mut q = p_minus_one.clone();
+    let mut s = 0;
+    while q.is_even() {
+        q >>= 1;
+        s += 1;
+    }
+    let mut z = BigUint::from(2u32);
+    while z.modpow(&half, p) != p_minus_one {
+        z += 1u32;
+    }
+
+    let mut m = s;
+    let mut c = z.modpow(&q, p);
+    let mut t = n.modpow(&q, p);
+    let mut r = n.modpow(&((&q + 1u32) >> 1), p);
+    while t != one {
+        // find the least i with t^(2^i) = 1
+        let mut i = 0;
+        let mut t2 = t.clone();
+        while t2 != one {
+            t2 = &t2 * &t2 % p;
+            i += 1;
+        }
+        let b = c.modpow(&(BigUint::one() << (m - i - 1)), p);
+        m = i;
+        c = &b * &b % p;
+        t = t * &c % p;
+        r = r * b % p;
+    }
+    Some(r)
+}
+
+/// Find `x` with `g^x = y mod p` by the Pohlig-Hellman algorithm, where `factors` are the
+/// distinct primes whose product is `p - 1`. Each residue of `x` modulo one of the factors is
+/// found by brute force in the subgroup of that order, so they should all be small. Returns
+/// `None` if `y` isn't a power of `g`.
+///
+/// ```
+/// use arse::math::pohlig_hellman;
+/// use num_bigint::BigUint;
+///
+/// let (g, p) = (BigUint::from(5u32), BigUint::from(23u32));
+/// let x = pohlig_hellman(&g, &BigUint::from(17u32), &p, &[2, 11]).unwrap();
+/// assert_eq!(x, BigUint::from(7u32));
+/// ```
+pub fn pohlig_hellman(g: &BigUint, y: &BigUint, p: &BigUint, factors: &[u32]) -> Option<BigUint> {
+    let order = p - 1u32;
+    let residues = factors
+        .iter()
+        .map(|&r| {
+            let cofactor = &order / r;
+            let g_r = g.modpow(&cofactor, p);
+            let y_r = y.modpow(&cofactor, p);
+            let mut power = BigUint::one();
+            for k in 0..r {
+                if power == y_r {
+                    return Some((BigUint::from(k), BigUint::from(r)));
+                }
+                power = power * &g_r % p;
+            }
+            None
+        })
+        .collect::<Option<Vec<_>>>()?;
+    crt(&residues).map(|(x, _)| x)
+}
@@ -0,0 +1,201 @@
+/// A toy Merkle-Damgård hash with a deliberately tiny state, plus the generic attacks on iterated
+/// hashes built on top of it.
+///
+/// The compression function encrypts each message block with AES-128, keyed by the current state,
+/// and truncates the result back down to the state size. Keeping the state to a couple of dozen
+/// bits puts birthday and brute-force searches within easy reach.
+use crate::encrypt::{aes128_encrypt_block, BLOCK_SIZE};
+use rand::{thread_rng, RngCore};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub mod expandable;
+pub mod herding;
+pub mod multicollision;
+
+/// A toy Merkle-Damgård hash over `BLOCK_SIZE`-byte blocks with a state of up to 32 bits.
+///
+/// The hash counts every call to its compression function, so that attacks can report how much
+/// work they did.
+#[derive(Debug)]
+pub struct ToyHash {
+    bits: u32,
+    iv: u32,
+    calls: AtomicUsize,
+}
+
+impl ToyHash {
+    /// Create a hash with a `bits`-bit state and an arbitrary fixed initial value.
+    ///
+    /// Panics if `bits` is zero or more than 32.
+    pub fn new(bits: u32) -> Self {
+        ToyHash::with_iv(bits, 0x5eed_cafe)
+    }
+
+    /// Create a hash with a `bits`-bit state and the given initial value, truncated to fit.
+    ///
+    /// Panics if `bits` is zero or more than 32.
+    pub fn with_iv(bits: u32, iv: u32) -> Self {
+        assert!(
+            bits > 0 && bits <= 32,
+            "state must be between 1 and 32 bits"
+        );
+        ToyHash {
+            bits,
+            iv: iv & (u32::MAX >> (32 - bits)),
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    /// The size of the state, in bits.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// The initial state.
+    pub fn iv(&self) -> u32 {
+        self.iv
+    }
+
+    /// The number of times the compression function has been called.
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// Reset the compression function call count to zero.
+    pub fn reset_calls(&self) {
+        self.calls.store(0, Ordering::Relaxed);
+    }
+
+    /// Run the compression function over a single block, starting from `state`.
+    ///
+    /// Panics if `block` isn't exactly `BLOCK_SIZE` bytes long.
+    pub fn compress(&self, state: u32, block: &[u8]) -> u32 {
+        assert_eq!(
+            block.len(),
+            BLOCK_SIZE,
+            "blocks must be {} bytes",
+            BLOCK_SIZE
+        );
+        self.calls.fetch_add(1, Ordering::Relaxed);
+
+        let mut key = [0u8; 16];
+        key[..4].copy_from_slice(&state.to_be_bytes());
+        let mut out = block.to_vec();
+        aes128_encrypt_block(&key, &mut out).expect("the key is always 16 bytes");
+
+        let mut word = [0u8; 4];
+        word.copy_from_slice(&out[..4]);
+        u32::from_be_bytes(word) >> (32 - self.bits)
+    }
+
+    /// Run the compression function over each block of `blocks` in turn, starting from `state`,
+    /// without any padding.
+    ///
+    /// Panics if `blocks` isn't a whole number of blocks long.
+    pub fn iterate(&self, state: u32, blocks: &[u8]) -> u32 {
+        assert!(
+            blocks.len() % BLOCK_SIZE == 0,
+            "input must be a whole number of blocks"
+        );
+        blocks
+            .chunks(BLOCK_SIZE)
+            .fold(state, |state, block| self.compress(state, block))
+    }
+
+    /// Hash `message`, with Merkle-Damgård strengthening.
+    pub fn digest(&self, message: &[u8]) -> u32 {
+        self.iterate(self.iv, &pad(message))
+    }
+}
+
+/// Pad `message` to a whole number of blocks: a single set bit, then zeroes, then the message
+/// length in bits as a big-endian 64-bit integer.
+///
+/// ```
+/// use arse::md::pad;
+///
+/// let padded = pad(b"YELLOW");
+/// assert_eq!(padded.len(), 16);
+/// assert_eq!(&padded[..7], b"YELLOW\x80");
+/// assert_eq!(&padded[8..], &48u64.to_be_bytes());
+/// ```
+pub fn pad(message: &[u8]) -> Vec<u8> {
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % BLOCK_SIZE != BLOCK_SIZE - 8 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&(message.len() as u64 * 8).to_be_bytes());
+    padded
+}
+
+/// Generate a random message block.
+pub fn random_block() -> Vec<u8> {
+    let mut block = vec![0u8; BLOCK_SIZE];
+    thread_rng().fill_bytes(&mut block);
+    block
+}
+
+/// A pair of distinct blocks which compress to the same state: either from a common starting
+/// state, or `a` from one state and `b` from another.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Collision {
+    pub a: Vec<u8>,
+    pub b: Vec<u8>,
+    pub state: u32,
+}
+
+/// Find a pair of distinct blocks which collide from `state`, by a birthday search over random
+/// blocks.
+pub fn find_collision(hash: &ToyHash, state: u32) -> Collision {
+    let mut seen = HashMap::new();
+    loop {
+        let block = random_block();
+        let next = hash.compress(state, &block);
+        match seen.insert(next, block.clone()) {
+            Some(other) if other != block => {
+                return Collision {
+                    a: other,
+                    b: block,
+                    state: next,
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Find a block `a` compressing from `from_a` and a block `b` compressing from `from_b` which
+/// arrive at the same state, by a birthday search over random blocks on both sides.
+pub fn find_collision_between(hash: &ToyHash, from_a: u32, from_b: u32) -> Collision {
+    if from_a == from_b {
+        return find_collision(hash, from_a);
+    }
+
+    let mut seen_a = HashMap::new();
+    let mut seen_b: HashMap<u32, Vec<u8>> = HashMap::new();
+    loop {
+        let a = random_block();
+        let next = hash.compress(from_a, &a);
+        if let Some(b) = seen_b.get(&next) {
+            return Collision {
+                a,
+                b: b.clone(),
+                state: next,
+            };
+        }
+        seen_a.insert(next, a);
+
+        let b = random_block();
+        let next = hash.compress(from_b, &b);
+        if let Some(a) = seen_a.get(&next) {
+            return Collision {
+                a: a.clone(),
+                b,
+                state: next,
+            };
+        }
+        seen_b.insert(next, b);
+    }
+}
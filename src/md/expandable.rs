@@ -0,0 +1,151 @@
+/// Kelsey and Schneier's expandable messages, and the second-preimage attack they enable on long
+/// messages.
+///
+/// A long message passes through a great many intermediate states, and hitting any one of them
+/// is far cheaper than hitting the final hash. Merkle-Damgård strengthening means a forgery
+/// which does so also needs to match the original's length, which is what an expandable message
+/// provides: a prefix which can be made any length in a range while always leaving the hash in
+/// the same state.
+use super::{find_collision_between, random_block, ToyHash};
+use crate::encrypt::BLOCK_SIZE;
+use std::collections::HashMap;
+
+/// One step of an expandable message: a single block and a longer run of blocks which leave the
+/// hash in the same state.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Piece {
+    pub short: Vec<u8>,
+    pub long: Vec<u8>,
+}
+
+/// A `(k, k + 2^k - 1)`-expandable message: for any number of blocks in that range, a message of
+/// that many blocks leading to `state()`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExpandableMessage {
+    pub initial: u32,
+    /// The pieces, longest first: piece `i` chooses between 1 block and `2^(k-1-i) + 1` blocks.
+    pub pieces: Vec<Piece>,
+    state: u32,
+}
+
+impl ExpandableMessage {
+    /// Generate a `(k, k + 2^k - 1)`-expandable message starting from `initial`.
+    ///
+    /// Each of the `k` pieces costs a birthday search plus hashing `2^i` dummy blocks, so this is
+    /// cheap next to the `2^k` blocks of freedom it buys.
+    pub fn generate(hash: &ToyHash, initial: u32, k: usize) -> Self {
+        let mut pieces = Vec::with_capacity(k);
+        let mut state = initial;
+        for i in (0..k).rev() {
+            let dummy = vec![0u8; BLOCK_SIZE << i];
+            let collision = find_collision_between(hash, state, hash.iterate(state, &dummy));
+
+            let mut long = dummy;
+            long.extend_from_slice(&collision.b);
+            pieces.push(Piece {
+                short: collision.a,
+                long,
+            });
+            state = collision.state;
+        }
+
+        ExpandableMessage {
+            initial,
+            pieces,
+            state,
+        }
+    }
+
+    /// The number of pieces, `k`.
+    pub fn k(&self) -> usize {
+        self.pieces.len()
+    }
+
+    /// The shortest message available, in blocks.
+    pub fn min_blocks(&self) -> usize {
+        self.k()
+    }
+
+    /// The longest message available, in blocks.
+    pub fn max_blocks(&self) -> usize {
+        self.k() + (1 << self.k()) - 1
+    }
+
+    /// The state every message leaves the hash in.
+    pub fn state(&self) -> u32 {
+        self.state
+    }
+
+    /// A message exactly `blocks` blocks long, or `None` if that's out of range.
+    pub fn message(&self, blocks: usize) -> Option<Vec<u8>> {
+        if blocks < self.min_blocks() || blocks > self.max_blocks() {
+            return None;
+        }
+
+        let extra = blocks - self.k();
+        let message = self
+            .pieces
+            .iter()
+            .enumerate()
+            .flat_map(|(i, piece)| {
+                if extra >> (self.k() - 1 - i) & 1 == 0 {
+                    piece.short.iter()
+                } else {
+                    piece.long.iter()
+                }
+            })
+            .cloned()
+            .collect();
+        Some(message)
+    }
+}
+
+/// The state of the hash after each whole block of `message`, starting from `initial`; element `i`
+/// is the state after `i + 1` blocks.
+pub fn intermediate_states(hash: &ToyHash, initial: u32, message: &[u8]) -> Vec<u32> {
+    message
+        .chunks_exact(BLOCK_SIZE)
+        .scan(initial, |state, block| {
+            *state = hash.compress(*state, block);
+            Some(*state)
+        })
+        .collect()
+}
+
+/// Find a different message with the same length and hash as `target`.
+///
+/// Builds an expandable message just big enough to cover `target`, then searches for a bridge
+/// block taking its final state to one of the intermediate states of `target`. The forgery is an
+/// expandable message of the right length, the bridge, and the rest of `target`. Returns `None` if
+/// `target` is too short to leave room for an expandable message and bridge.
+pub fn second_preimage(hash: &ToyHash, target: &[u8]) -> Option<Vec<u8>> {
+    let blocks = target.len() / BLOCK_SIZE;
+    let k = (1..).find(|&k| k + (1 << k) >= blocks)?;
+    if blocks < k + 1 {
+        return None;
+    }
+
+    // the bridge replaces block `i` (counting from 1), which has to come after an expandable
+    // message of `i - 1` blocks
+    let mut targets = HashMap::new();
+    for (i, state) in intermediate_states(hash, hash.iv(), target)
+        .into_iter()
+        .enumerate()
+        .skip(k)
+    {
+        targets.entry(state).or_insert(i + 1);
+    }
+
+    let expandable = ExpandableMessage::generate(hash, hash.iv(), k);
+    let (bridge, i) = loop {
+        let block = random_block();
+        if let Some(&i) = targets.get(&hash.compress(expandable.state(), &block)) {
+            break (block, i);
+        }
+    };
+
+    let mut forgery = expandable.message(i - 1)?;
+    forgery.extend_from_slice(&bridge);
+    forgery.extend_from_slice(&target[i * BLOCK_SIZE..]);
+    Some(forgery)
+}
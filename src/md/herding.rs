@@ -0,0 +1,181 @@
+/// Kelsey and Kohno's herding attack, which commits to a hash before choosing the message.
+///
+/// A diamond structure is a binary tree of collisions: `2^k` leaf states are paired off and each
+/// pair is collided into a single state, halving the number of states at each level until only
+/// the root is left. Any message which can be linked into one of the leaves can then be steered
+/// to the root, so a hash committed to in advance can be made to fit whatever "prediction" turns
+/// out to be right.
+use super::{find_collision_between, pad, random_block, ToyHash};
+use crate::encrypt::BLOCK_SIZE;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// A diamond structure over a toy hash.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diamond {
+    /// The `2^k` states at the top of the tree.
+    pub leaves: Vec<u32>,
+    /// For each level, the block which takes each state on that level to the next one down, and
+    /// the state it leads to.
+    pub levels: Vec<HashMap<u32, (Vec<u8>, u32)>>,
+    /// Calls made to the compression function while building each level.
+    pub work: Vec<usize>,
+}
+
+/// Apply `f` to each of `items` using up to `threads` threads, keeping the results in order.
+fn parallel_map<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let chunk = items.len().div_ceil(threads.max(1)).max(1);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("worker thread panicked"))
+            .collect()
+    })
+}
+
+impl Diamond {
+    /// Build a diamond structure with `2^k` leaves, spreading the collision searches for each
+    /// level across `threads` threads.
+    pub fn generate(hash: &ToyHash, k: usize, threads: usize) -> Self {
+        let mut seen = HashSet::new();
+        let mut leaves = Vec::with_capacity(1 << k);
+        while leaves.len() < 1 << k {
+            let state = hash.compress(hash.iv(), &random_block());
+            if seen.insert(state) {
+                leaves.push(state);
+            }
+        }
+
+        let mut levels = Vec::with_capacity(k);
+        let mut work = Vec::with_capacity(k);
+        let mut states = leaves.clone();
+        while states.len() > 1 {
+            let start = hash.calls();
+            let pairs = states
+                .chunks(2)
+                .map(|pair| (pair[0], pair[1]))
+                .collect::<Vec<(u32, u32)>>();
+            let collisions = parallel_map(&pairs, threads, |&(a, b)| {
+                find_collision_between(hash, a, b)
+            });
+            work.push(hash.calls() - start);
+
+            let mut level = HashMap::new();
+            states = Vec::with_capacity(collisions.len());
+            for ((a, b), collision) in pairs.into_iter().zip(collisions) {
+                level.insert(a, (collision.a, collision.state));
+                level.insert(b, (collision.b, collision.state));
+                states.push(collision.state);
+            }
+            levels.push(level);
+        }
+
+        Diamond {
+            leaves,
+            levels,
+            work,
+        }
+    }
+
+    /// The depth of the tree, `k`.
+    pub fn k(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// The state at the bottom of the tree.
+    pub fn root(&self) -> u32 {
+        self.levels
+            .last()
+            .and_then(|level| level.values().next())
+            .map_or_else(|| self.leaves[0], |&(_, state)| state)
+    }
+
+    /// The `k` blocks which take `leaf` down to the root, or `None` if it isn't a leaf.
+    pub fn path(&self, leaf: u32) -> Option<Vec<u8>> {
+        let mut state = leaf;
+        let mut path = Vec::with_capacity(self.k() * BLOCK_SIZE);
+        for level in &self.levels {
+            let (block, next) = level.get(&state)?;
+            path.extend_from_slice(block);
+            state = *next;
+        }
+        Some(path)
+    }
+
+    /// The hash to commit to for predictions `prediction_len` bytes long.
+    ///
+    /// A herded message is the prediction, one linking block and the path through the tree, so
+    /// its length (and therefore its final padding block) is fixed in advance. Panics if
+    /// `prediction_len` isn't a whole number of blocks.
+    pub fn commitment(&self, hash: &ToyHash, prediction_len: usize) -> u32 {
+        assert!(
+            prediction_len % BLOCK_SIZE == 0,
+            "predictions must be a whole number of blocks"
+        );
+        let len = prediction_len + (1 + self.k()) * BLOCK_SIZE;
+        let padding = pad(&vec![0u8; len]).split_off(len);
+        hash.iterate(self.root(), &padding)
+    }
+}
+
+/// Find a block taking `state` to one of the leaves of `diamond`, searching with `threads`
+/// threads. Returns the block and the leaf it reaches.
+pub fn find_link(hash: &ToyHash, diamond: &Diamond, state: u32, threads: usize) -> (Vec<u8>, u32) {
+    let leaves = diamond.leaves.iter().cloned().collect::<HashSet<u32>>();
+    let found = AtomicBool::new(false);
+    let link = Mutex::new(None);
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| {
+                while !found.load(Ordering::Relaxed) {
+                    let block = random_block();
+                    let next = hash.compress(state, &block);
+                    if leaves.contains(&next) {
+                        found.store(true, Ordering::Relaxed);
+                        *link.lock().expect("worker thread panicked") = Some((block, next));
+                    }
+                }
+            });
+        }
+    });
+
+    link.into_inner()
+        .expect("worker thread panicked")
+        .expect("a link is always found before the workers stop")
+}
+
+/// Herd `prediction` into `diamond`, producing a message which starts with `prediction` and
+/// hashes to `diamond.commitment(hash, prediction.len())`.
+///
+/// Returns `None` if `prediction` isn't a whole number of blocks.
+pub fn herd(
+    hash: &ToyHash,
+    diamond: &Diamond,
+    prediction: &[u8],
+    threads: usize,
+) -> Option<Vec<u8>> {
+    if prediction.len() % BLOCK_SIZE != 0 {
+        return None;
+    }
+
+    let state = hash.iterate(hash.iv(), prediction);
+    let (link, leaf) = find_link(hash, diamond, state, threads);
+
+    let mut message = prediction.to_vec();
+    message.extend_from_slice(&link);
+    message.extend_from_slice(&diamond.path(leaf)?);
+    Some(message)
+}
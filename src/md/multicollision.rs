@@ -0,0 +1,117 @@
+/// Joux multicollisions, and the collision they give in the concatenation of two iterated hashes.
+///
+/// Each of `n` successive birthday searches finds a pair of blocks which collide from the state
+/// left by the previous pair. Choosing either block at each step gives `2^n` distinct messages
+/// which all hash to the same value, for only `n` times the work of a single collision.
+use super::{find_collision, Collision, ToyHash};
+use std::collections::HashMap;
+
+/// A `2^n`-way multicollision: `n` colliding block pairs, chained one after another.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Multicollision {
+    pub initial: u32,
+    pub collisions: Vec<Collision>,
+}
+
+impl Multicollision {
+    /// Generate a `2^n`-way multicollision from `initial` with `n` birthday searches.
+    pub fn generate(hash: &ToyHash, initial: u32, n: usize) -> Self {
+        let mut multicollision = Multicollision {
+            initial,
+            collisions: Vec::with_capacity(n),
+        };
+        for _ in 0..n {
+            multicollision.extend(hash);
+        }
+        multicollision
+    }
+
+    /// Double the number of colliding messages with one more birthday search.
+    pub fn extend(&mut self, hash: &ToyHash) {
+        let collision = find_collision(hash, self.state());
+        self.collisions.push(collision);
+    }
+
+    /// The number of colliding block pairs, `n`.
+    pub fn levels(&self) -> usize {
+        self.collisions.len()
+    }
+
+    /// The number of colliding messages, `2^n`.
+    pub fn count(&self) -> usize {
+        1 << self.levels()
+    }
+
+    /// The state every colliding message leaves the hash in.
+    pub fn state(&self) -> u32 {
+        self.collisions.last().map_or(self.initial, |c| c.state)
+    }
+
+    /// The `index`th colliding message: bit `i` of `index` picks which block of the `i`th pair to
+    /// use.
+    ///
+    /// Panics if `index` isn't less than `count()`.
+    pub fn message(&self, index: usize) -> Vec<u8> {
+        assert!(index < self.count(), "no such message");
+        self.collisions
+            .iter()
+            .enumerate()
+            .flat_map(|(i, c)| {
+                if index >> i & 1 == 0 {
+                    c.a.iter()
+                } else {
+                    c.b.iter()
+                }
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Iterate over all the colliding messages.
+    pub fn messages<'a>(&'a self) -> impl Iterator<Item = Vec<u8>> + 'a {
+        (0..self.count()).map(move |i| self.message(i))
+    }
+}
+
+/// A collision in `cheap(M) || expensive(M)`, along with the work it took to find.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CascadeCollision {
+    pub a: Vec<u8>,
+    pub b: Vec<u8>,
+    /// The number of collisions found in the cheap hash; the multicollision had `2^levels`
+    /// messages.
+    pub levels: usize,
+    /// Calls made to the cheap compression function.
+    pub cheap_calls: usize,
+    /// Calls made to the expensive compression function.
+    pub expensive_calls: usize,
+}
+
+/// Find a pair of messages which collide under both `cheap` and `expensive`.
+///
+/// Generates a multicollision in `cheap` big enough that a birthday collision in `expensive` is
+/// likely among its messages, then hashes them all with `expensive`. If no pair collides, the
+/// multicollision is doubled and the search repeated.
+pub fn cascade_collision(cheap: &ToyHash, expensive: &ToyHash) -> CascadeCollision {
+    let cheap_start = cheap.calls();
+    let expensive_start = expensive.calls();
+
+    let mut multicollision =
+        Multicollision::generate(cheap, cheap.iv(), (expensive.bits() / 2) as usize);
+    loop {
+        let mut seen = HashMap::new();
+        for message in multicollision.messages() {
+            let digest = expensive.digest(&message);
+            if let Some(other) = seen.insert(digest, message.clone()) {
+                return CascadeCollision {
+                    a: other,
+                    b: message,
+                    levels: multicollision.levels(),
+                    cheap_calls: cheap.calls() - cheap_start,
+                    expensive_calls: expensive.calls() - expensive_start,
+                };
+            }
+        }
+        multicollision.extend(cheap);
+    }
+}
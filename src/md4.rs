@@ -0,0 +1,140 @@
+/// MD4, implemented in-crate so that attacks can get at its intermediate states.
+///
+/// The compression function runs 48 steps, each updating one of the four state words in turn
+/// (`a`, `d`, `c`, `b`, `a`, ...). `steps` exposes the value produced by every step, which is
+/// what Wang's collision attack needs to check and massage its sufficient conditions.
+pub mod wang;
+
+/// The size of an MD4 message block, in bytes.
+pub const BLOCK_SIZE: usize = 64;
+
+/// The MD4 initial state, as `[a, b, c, d]`.
+pub const INITIAL_STATE: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+
+/// The additive constants for rounds two and three.
+const ROUND_2: u32 = 0x5a82_7999;
+const ROUND_3: u32 = 0x6ed9_eba1;
+
+/// The rotation applied at each step of a round, indexed by step modulo four.
+pub const SHIFTS: [[u32; 4]; 3] = [[3, 7, 11, 19], [3, 5, 9, 13], [3, 9, 11, 15]];
+
+/// The message word used at each step of rounds two and three.
+const ORDER_2: [usize; 16] = [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15];
+const ORDER_3: [usize; 16] = [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15];
+
+/// The round one boolean function: `x ? y : z`.
+pub fn f(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (!x & z)
+}
+
+/// The round two boolean function: the majority of `x`, `y` and `z`.
+pub fn g(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (x & z) | (y & z)
+}
+
+/// The round three boolean function: the parity of `x`, `y` and `z`.
+pub fn h(x: u32, y: u32, z: u32) -> u32 {
+    x ^ y ^ z
+}
+
+/// Split a 64-byte block into its sixteen little-endian message words.
+///
+/// Panics if `block` isn't exactly `BLOCK_SIZE` bytes long.
+pub fn words(block: &[u8]) -> [u32; 16] {
+    assert_eq!(
+        block.len(),
+        BLOCK_SIZE,
+        "blocks must be {} bytes",
+        BLOCK_SIZE
+    );
+    let mut words = [0u32; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    words
+}
+
+/// Join sixteen message words back into a 64-byte block.
+pub fn block(words: &[u32; 16]) -> Vec<u8> {
+    words
+        .iter()
+        .flat_map(|word| word.to_le_bytes().to_vec())
+        .collect()
+}
+
+/// Run the 48 steps of the compression function over `words`, starting from `state`.
+///
+/// The result starts with the initial state in step order (`a`, `d`, `c`, `b`) followed by the
+/// value produced by each step, so element `4 * i + 4` is `a_{i+1}`, `4 * i + 5` is `d_{i+1}`,
+/// and so on.
+pub fn steps(state: &[u32; 4], words: &[u32; 16]) -> Vec<u32> {
+    let [a, b, c, d] = *state;
+    let mut values = Vec::with_capacity(52);
+    values.extend_from_slice(&[a, d, c, b]);
+
+    for step in 0..48 {
+        let n = values.len();
+        // the word being replaced, then the other three in the order the step function takes
+        // them
+        let (old, x, y, z) = (values[n - 4], values[n - 1], values[n - 2], values[n - 3]);
+        let (round, i) = (step / 16, step % 16);
+        let sum = match round {
+            0 => old.wrapping_add(f(x, y, z)).wrapping_add(words[i]),
+            1 => old
+                .wrapping_add(g(x, y, z))
+                .wrapping_add(words[ORDER_2[i]])
+                .wrapping_add(ROUND_2),
+            _ => old
+                .wrapping_add(h(x, y, z))
+                .wrapping_add(words[ORDER_3[i]])
+                .wrapping_add(ROUND_3),
+        };
+        values.push(sum.rotate_left(SHIFTS[round][i % 4]));
+    }
+    values
+}
+
+/// Run the compression function over a single 64-byte block, updating `state`.
+pub fn compress(state: &mut [u32; 4], block: &[u8]) {
+    let values = steps(state, &words(block));
+    let n = values.len();
+    let [a, b, c, d] = state;
+    *a = a.wrapping_add(values[n - 4]);
+    *d = d.wrapping_add(values[n - 3]);
+    *c = c.wrapping_add(values[n - 2]);
+    *b = b.wrapping_add(values[n - 1]);
+}
+
+/// Pad `message` to a whole number of blocks: a single set bit, then zeroes, then the message
+/// length in bits as a little-endian 64-bit integer.
+pub fn pad(message: &[u8]) -> Vec<u8> {
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % BLOCK_SIZE != BLOCK_SIZE - 8 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&(message.len() as u64 * 8).to_le_bytes());
+    padded
+}
+
+/// Hash `message` with MD4.
+///
+/// ```
+/// use arse::encode::hex::ToHex;
+/// use arse::md4::digest;
+///
+/// assert_eq!(digest(b"").to_hex(), "31d6cfe0d16ae931b73c59d7e0c089c0");
+/// assert_eq!(digest(b"abc").to_hex(), "a448017aaf21d8525fc10ae87aa6729d");
+/// ```
+pub fn digest(message: &[u8]) -> [u8; 16] {
+    let mut state = INITIAL_STATE;
+    for block in pad(message).chunks(BLOCK_SIZE) {
+        compress(&mut state, block);
+    }
+
+    let mut out = [0u8; 16];
+    for (bytes, word) in out.chunks_mut(4).zip(state.iter()) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
@@ -0,0 +1,352 @@
+/// Wang, Lai, Feng, Chen and Yu's MD4 collision attack.
+///
+/// Two one-block messages differing by `ΔM = (m1 + 2^31, m2 + 2^31 - 2^28, m12 - 2^16)` collide
+/// with high probability when the intermediate states of the first satisfy a table of
+/// "sufficient conditions" on individual bits. Message modification forces most of them to hold:
+/// the round one conditions directly, by fixing up each state word and solving for the message
+/// word which produces it, and the first few round two conditions indirectly, by tweaking an
+/// early message word and then re-solving for the round one words after it so that nothing else
+/// changes. The rest are left to chance.
+use super::{block, compress, f, g, steps, words, INITIAL_STATE, SHIFTS};
+use rand::{thread_rng, Rng};
+
+/// A condition on one bit (counting from zero) of a state word.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Condition {
+    /// The bit must be clear.
+    Zero(u32),
+    /// The bit must be set.
+    One(u32),
+    /// The bit must equal the same bit of the state word this many steps earlier.
+    Equal(u32, usize),
+    /// The bit must differ from the same bit of the state word this many steps earlier.
+    Differ(u32, usize),
+}
+
+use self::Condition::{Differ, Equal, One, Zero};
+
+/// The sufficient conditions from table 6 of the paper, by step: step 1 produces `a1`, step 2
+/// `d1`, and so on, with round two starting at step 17 (`a5`).
+pub const CONDITIONS: &[(usize, &[Condition])] = &[
+    (1, &[Equal(6, 1)]),
+    (2, &[Zero(6), Equal(7, 1), Equal(10, 1)]),
+    (3, &[One(6), One(7), Zero(10), Equal(25, 1)]),
+    (4, &[One(6), Zero(7), Zero(10), Zero(25)]),
+    (5, &[One(7), One(10), Zero(25), Equal(13, 1)]),
+    (
+        6,
+        &[
+            Zero(13),
+            Equal(18, 1),
+            Equal(19, 1),
+            Equal(20, 1),
+            Equal(21, 1),
+            One(25),
+        ],
+    ),
+    (
+        7,
+        &[
+            Equal(12, 1),
+            Zero(13),
+            Equal(14, 1),
+            Zero(18),
+            Zero(19),
+            One(20),
+            Zero(21),
+        ],
+    ),
+    (
+        8,
+        &[
+            One(12),
+            One(13),
+            Zero(14),
+            Equal(16, 1),
+            Zero(18),
+            Zero(19),
+            Zero(20),
+            Zero(21),
+        ],
+    ),
+    (
+        9,
+        &[
+            One(12),
+            One(13),
+            One(14),
+            Zero(16),
+            Zero(18),
+            Zero(19),
+            Zero(20),
+            One(21),
+            Equal(22, 1),
+            Equal(25, 1),
+        ],
+    ),
+    (
+        10,
+        &[
+            One(12),
+            One(13),
+            One(14),
+            Zero(16),
+            Zero(19),
+            One(20),
+            One(21),
+            Zero(22),
+            One(25),
+            Equal(29, 1),
+        ],
+    ),
+    (
+        11,
+        &[
+            One(16),
+            Zero(19),
+            Zero(20),
+            Zero(21),
+            Zero(22),
+            Zero(25),
+            One(29),
+            Equal(31, 1),
+        ],
+    ),
+    (
+        12,
+        &[
+            Zero(19),
+            One(20),
+            One(21),
+            Equal(22, 1),
+            One(25),
+            Zero(29),
+            Zero(31),
+        ],
+    ),
+    (
+        13,
+        &[
+            Zero(22),
+            Zero(25),
+            Equal(26, 1),
+            Equal(28, 1),
+            One(29),
+            Zero(31),
+        ],
+    ),
+    (
+        14,
+        &[Zero(22), Zero(25), One(26), One(28), Zero(29), One(31)],
+    ),
+    (
+        15,
+        &[Equal(18, 1), One(22), One(25), Zero(26), Zero(28), Zero(29)],
+    ),
+    (16, &[Zero(18), One(25), One(26), One(28), Zero(29)]),
+    (17, &[Equal(18, 2), One(25), Zero(26), One(28), One(31)]),
+    (
+        18,
+        &[
+            Equal(18, 1),
+            Equal(25, 2),
+            Equal(26, 2),
+            Equal(28, 2),
+            Equal(31, 2),
+        ],
+    ),
+    (
+        19,
+        &[
+            Equal(25, 1),
+            Equal(26, 1),
+            Equal(28, 1),
+            Equal(29, 1),
+            Equal(31, 1),
+        ],
+    ),
+    (20, &[Equal(28, 1), One(29), Zero(31)]),
+    (21, &[One(28), One(31)]),
+    (22, &[Equal(28, 2)]),
+    (23, &[Equal(28, 1), Differ(29, 1), Differ(31, 1)]),
+    (36, &[One(31)]),
+    (37, &[One(31)]),
+];
+
+/// The index into the output of `steps` of the word produced by `step`.
+fn index(step: usize) -> usize {
+    step + 3
+}
+
+fn conditions(step: usize) -> &'static [Condition] {
+    CONDITIONS
+        .iter()
+        .find(|&&(s, _)| s == step)
+        .map_or(&[], |&(_, conditions)| conditions)
+}
+
+/// Force the conditions for `step` to hold on `value`, where `earlier` holds every word produced
+/// before it.
+fn enforce(step: usize, value: u32, earlier: &[u32]) -> u32 {
+    let back = |n: usize| earlier[earlier.len() - n];
+    conditions(step)
+        .iter()
+        .fold(value, |value, &condition| match condition {
+            Zero(bit) => value & !(1 << bit),
+            One(bit) => value | 1 << bit,
+            Equal(bit, n) => value & !(1 << bit) | back(n) & 1 << bit,
+            Differ(bit, n) => value & !(1 << bit) | !back(n) & 1 << bit,
+        })
+}
+
+/// Whether the conditions for `step` hold in `values`, as produced by `steps`.
+fn holds(step: usize, values: &[u32]) -> bool {
+    let i = index(step);
+    enforce(step, values[i], &values[..i]) == values[i]
+}
+
+/// Whether every condition up to and including `step` holds in `values`.
+fn holds_through(step: usize, values: &[u32]) -> bool {
+    (1..=step).all(|s| holds(s, values))
+}
+
+/// The number of steps up to and including `through` whose conditions hold for message `words`.
+pub fn satisfied(words: &[u32; 16], through: usize) -> usize {
+    let values = steps(&INITIAL_STATE, words);
+    CONDITIONS
+        .iter()
+        .filter(|&&(step, _)| step <= through && holds(step, &values))
+        .count()
+}
+
+/// The message word which round one step `step` (counting from 1) needs to produce `value`,
+/// given the words produced before it.
+fn solve_round_one(step: usize, value: u32, earlier: &[u32]) -> u32 {
+    let n = earlier.len();
+    let (old, x, y, z) = (
+        earlier[n - 4],
+        earlier[n - 1],
+        earlier[n - 2],
+        earlier[n - 3],
+    );
+    value
+        .rotate_right(SHIFTS[0][(step - 1) % 4])
+        .wrapping_sub(old)
+        .wrapping_sub(f(x, y, z))
+}
+
+/// Single-step modification: rewrite `words` so that every round one condition holds.
+fn modify_round_one(words: &mut [u32; 16]) {
+    let mut values = steps(&INITIAL_STATE, words);
+    values.truncate(4);
+    for step in 1..=16 {
+        let n = values.len();
+        let (old, x, y, z) = (values[n - 4], values[n - 1], values[n - 2], values[n - 3]);
+        let value = old
+            .wrapping_add(f(x, y, z))
+            .wrapping_add(words[step - 1])
+            .rotate_left(SHIFTS[0][(step - 1) % 4]);
+        let value = enforce(step, value, &values);
+        words[step - 1] = solve_round_one(step, value, &values);
+        values.push(value);
+    }
+}
+
+/// Multi-step modification for a round two step which uses message word `word`: pick the value
+/// of `word` that makes the step's conditions hold, then re-solve for the four round one words
+/// after it so that the rest of round one is unchanged.
+///
+/// The change to `word` also changes the round one state word it produced, which can break that
+/// word's own conditions; if any earlier condition no longer holds the modification is undone.
+fn modify_round_two(words: &mut [u32; 16], step: usize, word: usize) {
+    let original = *words;
+    let mut values = steps(&INITIAL_STATE, words);
+    let i = index(step);
+    let target = enforce(step, values[i], &values[..i]);
+    if target == values[i] {
+        return;
+    }
+
+    // solve the round two step for the message word
+    let (old, x, y, z) = (values[i - 4], values[i - 1], values[i - 2], values[i - 3]);
+    words[word] = target
+        .rotate_right(SHIFTS[1][(step - 1) % 4])
+        .wrapping_sub(old)
+        .wrapping_sub(g(x, y, z))
+        .wrapping_sub(super::ROUND_2);
+
+    // recompute the round one word it feeds, then keep the next four round one words as they were
+    let r = index(word + 1);
+    let (old, x, y, z) = (values[r - 4], values[r - 1], values[r - 2], values[r - 3]);
+    values[r] = old
+        .wrapping_add(f(x, y, z))
+        .wrapping_add(words[word])
+        .rotate_left(SHIFTS[0][word % 4]);
+    for step in word + 2..(word + 6).min(17) {
+        let j = index(step);
+        words[step - 1] = solve_round_one(step, values[j], &values[..j]);
+    }
+
+    if !holds_through(step, &steps(&INITIAL_STATE, words)) {
+        *words = original;
+    }
+}
+
+/// Massage `words` so that the round one conditions and as many of the first round two conditions
+/// as possible hold.
+pub fn massage(words: &mut [u32; 16]) {
+    modify_round_one(words);
+    // a5 is produced from m0, and d5 from m4
+    modify_round_two(words, 17, 0);
+    modify_round_two(words, 18, 4);
+}
+
+/// Apply the collision differential to `words`.
+pub fn differential(words: &[u32; 16]) -> [u32; 16] {
+    let mut other = *words;
+    other[1] = other[1].wrapping_add(1 << 31);
+    other[2] = other[2].wrapping_add((1 << 31) - (1 << 28));
+    other[12] = other[12].wrapping_sub(1 << 16);
+    other
+}
+
+/// A pair of distinct one-block messages with the same MD4 hash.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Collision {
+    pub m: Vec<u8>,
+    pub m_prime: Vec<u8>,
+    /// The number of candidate messages tried.
+    pub attempts: usize,
+}
+
+/// Search for a collision by massaging random messages until one collides with its partner under
+/// the differential.
+pub fn find_collision() -> Collision {
+    let mut rng = thread_rng();
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let mut words = [0u32; 16];
+        rng.fill(&mut words[..]);
+        massage(&mut words);
+
+        let (m, m_prime) = (block(&words), block(&differential(&words)));
+        let (mut state, mut state_prime) = (INITIAL_STATE, INITIAL_STATE);
+        compress(&mut state, &m);
+        compress(&mut state_prime, &m_prime);
+        if state == state_prime {
+            return Collision {
+                m,
+                m_prime,
+                attempts,
+            };
+        }
+    }
+}
+
+/// Check that `m` and `m_prime` are one-block messages differing by exactly the differential.
+pub fn is_differential_pair(m: &[u8], m_prime: &[u8]) -> bool {
+    m.len() == super::BLOCK_SIZE
+        && m_prime.len() == super::BLOCK_SIZE
+        && differential(&words(m)) == words(m_prime)
+}
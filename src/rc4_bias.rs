@@ -0,0 +1,113 @@
+/// Plaintext recovery from RC4's single-byte keystream biases.
+///
+/// The early bytes of RC4's keystream aren't uniformly distributed. Two of the better-known
+/// biases: the 16th byte is 240 slightly more often than it should be, and the 32nd is 224. So
+/// if the same plaintext is encrypted under enough different keys, the most common ciphertext
+/// byte at those positions is the plaintext byte XORed with the biased value. Prefixing the
+/// secret with attacker-controlled padding slides each of its bytes into those positions in
+/// turn.
+use crate::encrypt::rc4_apply;
+use crate::stat::Histogram;
+use rand::{thread_rng, RngCore};
+use std::collections::HashMap;
+
+/// A biased keystream position (counting from zero) and the value it favours.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Bias {
+    pub position: usize,
+    pub value: u8,
+}
+
+/// The 16th keystream byte favours 240.
+pub const Z16: Bias = Bias {
+    position: 15,
+    value: 240,
+};
+
+/// The 32nd keystream byte favours 224.
+pub const Z32: Bias = Bias {
+    position: 31,
+    value: 224,
+};
+
+/// An oracle which appends a secret cookie to a request and encrypts the lot with RC4 under a
+/// fresh random key each time.
+pub struct CookieOracle {
+    cookie: Vec<u8>,
+}
+
+impl CookieOracle {
+    /// Create an oracle which appends `cookie` to every request.
+    pub fn new(cookie: &[u8]) -> Self {
+        CookieOracle {
+            cookie: cookie.to_vec(),
+        }
+    }
+
+    /// Encrypt `request || cookie` under a fresh random 128-bit key.
+    pub fn encrypt(&self, request: &[u8]) -> Vec<u8> {
+        let mut key = [0u8; 16];
+        thread_rng().fill_bytes(&mut key);
+
+        let mut plaintext = request.to_vec();
+        plaintext.extend_from_slice(&self.cookie);
+        rc4_apply(&key, &plaintext).expect("a 16-byte key is always valid")
+    }
+}
+
+/// Guess the plaintext bytes under the `Z16` and `Z32` biases when the cookie is preceded by
+/// `prefix_len` bytes of padding, from `samples` encryptions through `oracle`.
+///
+/// These are bytes `15 - prefix_len` and `31 - prefix_len` of the cookie; either guess is `None`
+/// if the ciphertexts don't reach that far.
+pub fn recover_at<O>(oracle: &mut O, prefix_len: usize, samples: usize) -> (Option<u8>, Option<u8>)
+where
+    O: FnMut(&[u8]) -> Vec<u8>,
+{
+    let prefix = vec![b'A'; prefix_len];
+    let mut z16 = Histogram(HashMap::new());
+    let mut z32 = Histogram(HashMap::new());
+
+    for _ in 0..samples {
+        let ciphertext = oracle(&prefix);
+        if let Some(&c) = ciphertext.get(Z16.position) {
+            z16.insert(c);
+        }
+        if let Some(&c) = ciphertext.get(Z32.position) {
+            z32.insert(c);
+        }
+    }
+
+    (
+        z16.most_common().map(|c| c ^ Z16.value),
+        z32.most_common().map(|c| c ^ Z32.value),
+    )
+}
+
+/// Recover a cookie of `cookie_len` bytes from `oracle`, with `samples` encryptions for each of
+/// the sixteen padding lengths.
+///
+/// The first sixteen bytes of the cookie are read through the `Z16` bias and the rest through
+/// `Z32`, so cookies longer than 32 bytes are out of reach and give `None`. Reliable recovery
+/// takes something like 2^23 samples per padding length; fewer will still give a best guess,
+/// just with some bytes wrong.
+pub fn recover_cookie<O>(mut oracle: O, cookie_len: usize, samples: usize) -> Option<Vec<u8>>
+where
+    O: FnMut(&[u8]) -> Vec<u8>,
+{
+    if cookie_len > Z32.position + 1 {
+        return None;
+    }
+
+    let mut cookie = vec![0u8; cookie_len];
+    for prefix_len in 0..=Z16.position {
+        let (z16, z32) = recover_at(&mut oracle, prefix_len, samples);
+        for (bias, guess) in [(Z16, z16), (Z32, z32)].iter() {
+            let i = bias.position - prefix_len;
+            if let (Some(byte), Some(guess)) = (cookie.get_mut(i), guess) {
+                *byte = *guess;
+            }
+        }
+    }
+    Some(cookie)
+}
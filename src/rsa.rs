@@ -0,0 +1,89 @@
+/// Textbook RSA, plus the oracles and attacks built on top of it.
+use crate::math::{gen_prime, invmod};
+use num_bigint::BigUint;
+use num_traits::One;
+
+pub mod bleichenbacher;
+pub mod parity;
+pub mod pkcs1;
+pub mod signature;
+pub mod unpadded;
+
+/// The public half of an RSA key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PublicKey {
+    pub e: BigUint,
+    pub n: BigUint,
+}
+
+/// The private half of an RSA key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrivateKey {
+    pub d: BigUint,
+    pub n: BigUint,
+}
+
+/// Generate a keypair whose modulus is `bits` bits long, using the public exponent `e`.
+///
+/// Primes are regenerated until `e` is invertible modulo the totient.
+pub fn generate_keypair(bits: usize, e: &BigUint) -> (PublicKey, PrivateKey) {
+    let one = BigUint::one();
+    loop {
+        let p = gen_prime(bits / 2);
+        let q = gen_prime(bits - bits / 2);
+        let n = &p * &q;
+        if p == q || n.bits() != bits {
+            continue;
+        }
+
+        let et = (&p - &one) * (&q - &one);
+        if let Some(d) = invmod(e, &et) {
+            return (
+                PublicKey {
+                    e: e.clone(),
+                    n: n.clone(),
+                },
+                PrivateKey { d, n },
+            );
+        }
+    }
+}
+
+impl PublicKey {
+    /// Raw RSA encryption (or signature verification): `m^e mod n`.
+    pub fn encrypt(&self, m: &BigUint) -> BigUint {
+        m.modpow(&self.e, &self.n)
+    }
+
+    /// The length of the modulus in bytes.
+    pub fn size(&self) -> usize {
+        self.n.bits().div_ceil(8)
+    }
+}
+
+impl PrivateKey {
+    /// Raw RSA decryption (or signing): `c^d mod n`.
+    pub fn decrypt(&self, c: &BigUint) -> BigUint {
+        c.modpow(&self.d, &self.n)
+    }
+
+    /// The length of the modulus in bytes.
+    pub fn size(&self) -> usize {
+        self.n.bits().div_ceil(8)
+    }
+}
+
+/// Encode `value` big-endian, left-padded with zeroes to `len` bytes.
+///
+/// ```
+/// use arse::rsa::to_bytes_padded;
+/// use num_bigint::BigUint;
+///
+/// assert_eq!(to_bytes_padded(&BigUint::from(0x0102u32), 4), vec![0, 0, 1, 2]);
+/// ```
+pub fn to_bytes_padded(value: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    let mut out = vec![0u8; len.saturating_sub(bytes.len())];
+    out.extend_from_slice(&bytes);
+    out
+}
@@ -0,0 +1,323 @@
+/// Bleichenbacher's 1998 PKCS#1 v1.5 padding oracle attack.
+///
+/// An oracle which reveals whether a ciphertext decrypts to a block starting `00 02` tells us that
+/// the plaintext lies in `[2B, 3B)`, where `B = 2^(8(k-2))`. By multiplying the target plaintext
+/// by chosen values `s` (via `c * s^e`) and asking the oracle about each result, the set of
+/// intervals the plaintext could lie in is narrowed until only one value remains.
+///
+/// Step and variable names follow the paper.
+use super::{to_bytes_padded, PrivateKey, PublicKey};
+use crate::math::{invmod, rand_range};
+use num_bigint::BigUint;
+use num_traits::{Num, One, Zero};
+use std::fmt;
+use std::str::FromStr;
+
+/// A server which decrypts a ciphertext and reveals only whether it is PKCS#1 v1.5 conforming;
+/// that is, whether it begins with `00 02`.
+pub struct PaddingOracle {
+    key: PrivateKey,
+}
+
+impl PaddingOracle {
+    /// Create a new oracle which decrypts with `key`.
+    pub fn new(key: PrivateKey) -> Self {
+        PaddingOracle { key }
+    }
+
+    /// Decrypt `c`, and report whether the plaintext begins with `00 02`.
+    pub fn is_conforming(&self, c: &BigUint) -> bool {
+        to_bytes_padded(&self.key.decrypt(c), self.key.size()).starts_with(&[0x00, 0x02])
+    }
+}
+
+/// The outcome of running the attack.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Progress {
+    /// The attack has finished, and recovered this (padded) plaintext.
+    Done(BigUint),
+    /// The oracle call budget ran out before the attack finished. It may be resumed.
+    Interrupted,
+}
+
+/// The complete state of an attack in progress.
+///
+/// The state can be written out with `to_string()` and read back with `parse()`, so that a long
+/// attack can be stopped and picked up again later.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attack {
+    public: PublicKey,
+    /// The blinded ciphertext being attacked, `c * s0^e`.
+    c0: BigUint,
+    /// The blinding factor applied in step 1.
+    s0: BigUint,
+    /// The iteration number, starting at one.
+    i: usize,
+    /// The next value of `s` to submit to the oracle.
+    next_s: BigUint,
+    /// During step 2c, the value of `r` being searched.
+    r: Option<BigUint>,
+    /// The set of intervals which may contain the plaintext.
+    intervals: Vec<(BigUint, BigUint)>,
+    /// The total number of oracle calls made.
+    calls: usize,
+}
+
+fn ceil_div(a: &BigUint, b: &BigUint) -> BigUint {
+    (a + b - BigUint::one()) / b
+}
+
+impl Attack {
+    /// Begin an attack on `c`, encrypted to `public`.
+    ///
+    /// If `c` is not already PKCS conforming, this first blinds it with random values until it is
+    /// (step 1), which consumes oracle calls of its own.
+    pub fn new<O>(public: &PublicKey, c: &BigUint, mut oracle: O) -> Self
+    where
+        O: FnMut(&BigUint) -> bool,
+    {
+        let n = &public.n;
+        let mut calls = 1;
+        let mut s0 = BigUint::one();
+        let mut c0 = c.clone();
+        while !oracle(&c0) {
+            s0 = rand_range(&BigUint::one(), n);
+            c0 = c * public.encrypt(&s0) % n;
+            calls += 1;
+        }
+
+        let b = Self::bound(public);
+        Attack {
+            public: public.clone(),
+            c0,
+            s0,
+            i: 1,
+            next_s: ceil_div(n, &(&b * 3u32)),
+            r: None,
+            intervals: vec![(&b * 2u32, &b * 3u32 - 1u32)],
+            calls,
+        }
+    }
+
+    fn bound(public: &PublicKey) -> BigUint {
+        BigUint::one() << (8 * (public.size() - 2))
+    }
+
+    /// The number of oracle calls made so far, including those made before any interruption.
+    pub fn calls(&self) -> usize {
+        self.calls
+    }
+
+    /// The number of iterations of steps 2 through 4 completed so far.
+    pub fn iterations(&self) -> usize {
+        self.i - 1
+    }
+
+    /// The set of intervals the plaintext may currently lie in.
+    pub fn intervals(&self) -> &[(BigUint, BigUint)] {
+        &self.intervals
+    }
+
+    /// Run the attack until the plaintext is recovered, or until `max_calls` further oracle calls
+    /// have been made.
+    pub fn run<O>(&mut self, mut oracle: O, max_calls: Option<usize>) -> Progress
+    where
+        O: FnMut(&BigUint) -> bool,
+    {
+        let n = self.public.n.clone();
+        let limit = max_calls.map(|m| self.calls + m);
+
+        loop {
+            // step 4: a single interval of width one means we're done
+            if let [(a, b)] = self.intervals.as_slice() {
+                if a == b {
+                    let s0_inv = invmod(&self.s0, &n).expect("blinding factor is invertible");
+                    return Progress::Done(a * s0_inv % &n);
+                }
+            }
+
+            // step 2: search for the next conforming s
+            let s = loop {
+                if limit.is_some_and(|l| self.calls >= l) {
+                    return Progress::Interrupted;
+                }
+
+                if let Some(r) = self.r.clone() {
+                    // step 2c: a single interval remains, so search r and s together
+                    let (a, b) = self.intervals[0].clone();
+                    let s_high = ceil_div(&(self.three_b() + &r * &n), &a);
+                    if self.next_s >= s_high {
+                        let r = r + 1u32;
+                        self.next_s = ceil_div(&(self.two_b() + &r * &n), &b);
+                        self.r = Some(r);
+                        continue;
+                    }
+                }
+
+                // steps 2a and 2b are a simple linear search
+                let s = self.next_s.clone();
+                self.next_s += 1u32;
+                self.calls += 1;
+                if oracle(&(&self.c0 * self.public.encrypt(&s) % &n)) {
+                    break s;
+                }
+            };
+
+            // step 3: narrow the set of solutions
+            self.narrow(&s);
+            self.i += 1;
+
+            // and set up the next search
+            if self.intervals.len() == 1 {
+                let b = self.intervals[0].1.clone();
+                let r = ceil_div(&((&b * &s - self.two_b()) * 2u32), &n);
+                self.next_s = ceil_div(&(self.two_b() + &r * &n), &b);
+                self.r = Some(r);
+            } else {
+                self.next_s = s + 1u32;
+                self.r = None;
+            }
+        }
+    }
+
+    fn two_b(&self) -> BigUint {
+        Self::bound(&self.public) * 2u32
+    }
+
+    fn three_b(&self) -> BigUint {
+        Self::bound(&self.public) * 3u32
+    }
+
+    fn narrow(&mut self, s: &BigUint) {
+        let n = &self.public.n;
+        let two_b = self.two_b();
+        let three_b = self.three_b();
+
+        let mut next: Vec<(BigUint, BigUint)> = Vec::new();
+        for (a, b) in self.intervals.iter() {
+            let low = a * s + 1u32;
+            let r_low = if low > three_b {
+                ceil_div(&(low - &three_b), n)
+            } else {
+                BigUint::zero()
+            };
+            let r_high = (b * s - &two_b) / n;
+
+            let mut r = r_low;
+            while r <= r_high {
+                let new_a = ceil_div(&(&two_b + &r * n), s).max(a.clone());
+                let new_b = ((&three_b - 1u32 + &r * n) / s).min(b.clone());
+                if new_a <= new_b {
+                    insert_interval(&mut next, new_a, new_b);
+                }
+                r += 1u32;
+            }
+        }
+        self.intervals = next;
+    }
+}
+
+/// Add `[a, b]` to a sorted set of disjoint intervals, merging any it overlaps.
+fn insert_interval(set: &mut Vec<(BigUint, BigUint)>, mut a: BigUint, mut b: BigUint) {
+    let mut merged = Vec::with_capacity(set.len() + 1);
+    for (x, y) in set.drain(..) {
+        if y < a || x > b {
+            merged.push((x, y));
+        } else {
+            a = a.min(x);
+            b = b.max(y);
+        }
+    }
+    merged.push((a, b));
+    merged.sort();
+    *set = merged;
+}
+
+/// Reasons a saved attack state could not be read back.
+#[derive(Debug, Eq, PartialEq)]
+pub enum StateParseError {
+    /// A required field was not present.
+    MissingField(&'static str),
+    /// A field's value could not be parsed.
+    BadValue(String),
+}
+
+impl fmt::Display for Attack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "n={:x}", self.public.n)?;
+        writeln!(f, "e={:x}", self.public.e)?;
+        writeln!(f, "c0={:x}", self.c0)?;
+        writeln!(f, "s0={:x}", self.s0)?;
+        writeln!(f, "i={}", self.i)?;
+        writeln!(f, "next_s={:x}", self.next_s)?;
+        if let Some(r) = &self.r {
+            writeln!(f, "r={:x}", r)?;
+        }
+        writeln!(f, "calls={}", self.calls)?;
+        for (a, b) in self.intervals.iter() {
+            writeln!(f, "interval={:x},{:x}", a, b)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Attack {
+    type Err = StateParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s
+            .lines()
+            .filter_map(|l| {
+                let mut parts = l.trim().splitn(2, '=');
+                Some((parts.next()?, parts.next()?))
+            })
+            .collect::<Vec<(&str, &str)>>();
+
+        let find = |name: &'static str| {
+            fields
+                .iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| *v)
+                .ok_or(StateParseError::MissingField(name))
+        };
+        let hex = |v: &str| {
+            BigUint::from_str_radix(v, 16).map_err(|_| StateParseError::BadValue(v.to_string()))
+        };
+        let dec = |v: &str| {
+            v.parse::<usize>()
+                .map_err(|_| StateParseError::BadValue(v.to_string()))
+        };
+
+        let intervals = fields
+            .iter()
+            .filter(|(k, _)| *k == "interval")
+            .map(|(_, v)| {
+                let mut ends = v.splitn(2, ',');
+                match (ends.next(), ends.next()) {
+                    (Some(a), Some(b)) => Ok((hex(a)?, hex(b)?)),
+                    _ => Err(StateParseError::BadValue(v.to_string())),
+                }
+            })
+            .collect::<Result<Vec<(BigUint, BigUint)>, StateParseError>>()?;
+        if intervals.is_empty() {
+            return Err(StateParseError::MissingField("interval"));
+        }
+
+        Ok(Attack {
+            public: PublicKey {
+                e: hex(find("e")?)?,
+                n: hex(find("n")?)?,
+            },
+            c0: hex(find("c0")?)?,
+            s0: hex(find("s0")?)?,
+            i: dec(find("i")?)?,
+            next_s: hex(find("next_s")?)?,
+            r: match find("r") {
+                Ok(r) => Some(hex(r)?),
+                Err(_) => None,
+            },
+            intervals,
+            calls: dec(find("calls")?)?,
+        })
+    }
+}
@@ -0,0 +1,67 @@
+/// RSA parity oracle decryption.
+///
+/// Doubling a plaintext is as easy as multiplying its ciphertext by `2^e`. Since the modulus is
+/// odd, `2m mod n` is even exactly when `2m` did not wrap, i.e. when `m < n/2`. Each query to an
+/// oracle revealing the low bit of a plaintext therefore halves the range the plaintext may lie
+/// in.
+use super::{PrivateKey, PublicKey};
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::Zero;
+
+/// A server which decrypts a ciphertext and reveals only whether the plaintext is even.
+pub struct ParityOracle {
+    key: PrivateKey,
+}
+
+impl ParityOracle {
+    /// Create a new oracle which decrypts with `key`.
+    pub fn new(key: PrivateKey) -> Self {
+        ParityOracle { key }
+    }
+
+    /// Decrypt `c`, and report whether the plaintext is even.
+    pub fn is_even(&self, c: &BigUint) -> bool {
+        self.key.decrypt(c).is_even()
+    }
+}
+
+/// Recover the plaintext of `c` by repeatedly doubling it and asking `is_even` about the result.
+///
+/// The bounds on the plaintext are tracked as exact fractions of `n`. After each query,
+/// `progress` is called with the integer part of the current upper bound, which converges on the
+/// plaintext from above one bit at a time.
+pub fn decrypt<O, P>(c: &BigUint, public: &PublicKey, mut is_even: O, mut progress: P) -> BigUint
+where
+    O: FnMut(&BigUint) -> bool,
+    P: FnMut(&BigUint),
+{
+    let n = &public.n;
+    let double = public.encrypt(&BigUint::from(2u32));
+    let two = BigRational::from_integer(BigInt::from(2));
+
+    let mut low = BigRational::zero();
+    let mut high = BigRational::from_integer(BigInt::from(n.clone()));
+    let mut c = c.clone();
+
+    for _ in 0..n.bits() {
+        c = c * &double % n;
+        let mid = (&low + &high) / &two;
+        if is_even(&c) {
+            high = mid;
+        } else {
+            low = mid;
+        }
+        progress(&to_biguint(&high.floor()));
+    }
+
+    // the interval is now narrower than one, and the plaintext is the only integer in it
+    to_biguint(&low.ceil())
+}
+
+fn to_biguint(r: &BigRational) -> BigUint {
+    r.to_integer()
+        .to_biguint()
+        .expect("plaintext bounds are never negative")
+}
@@ -0,0 +1,47 @@
+/// PKCS#1 v1.5 encryption padding.
+use rand::{thread_rng, Rng};
+
+/// Reasons padding or unpadding may fail.
+#[derive(Debug, Eq, PartialEq)]
+pub enum PaddingError {
+    /// The message is too long to pad to the requested length.
+    TooLong,
+    /// The block does not have the form `00 02 PS 00 M`.
+    Malformed,
+}
+
+/// Pad `message` to a `len`-byte block of the form `00 02 PS 00 M`, where `PS` is at least eight
+/// random non-zero bytes.
+///
+/// ```
+/// use arse::rsa::pkcs1::{pad, unpad};
+///
+/// let block = pad(b"kick it, CC", 32).unwrap();
+/// assert_eq!(block.len(), 32);
+/// assert_eq!(&block[..2], &[0x00, 0x02]);
+/// assert_eq!(unpad(&block).unwrap(), b"kick it, CC");
+/// assert!(pad(b"kick it, CC", 16).is_err());
+/// ```
+pub fn pad(message: &[u8], len: usize) -> Result<Vec<u8>, PaddingError> {
+    if message.len() + 11 > len {
+        return Err(PaddingError::TooLong);
+    }
+
+    let mut rng = thread_rng();
+    let mut block = vec![0x00, 0x02];
+    block.extend((0..len - message.len() - 3).map(|_| rng.gen_range(1u16, 256) as u8));
+    block.push(0x00);
+    block.extend_from_slice(message);
+    Ok(block)
+}
+
+/// Strip PKCS#1 v1.5 encryption padding from `block`, returning the message.
+pub fn unpad(block: &[u8]) -> Result<&[u8], PaddingError> {
+    if !block.starts_with(&[0x00, 0x02]) {
+        return Err(PaddingError::Malformed);
+    }
+    match block[2..].iter().position(|&b| b == 0x00) {
+        Some(sep) if sep >= 8 => Ok(&block[2 + sep + 1..]),
+        _ => Err(PaddingError::Malformed),
+    }
+}
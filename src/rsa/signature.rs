@@ -0,0 +1,198 @@
+/// PKCS#1 v1.5 signatures, and Bleichenbacher's e=3 forgery against sloppy verifiers.
+use super::{to_bytes_padded, PrivateKey, PublicKey};
+use num_bigint::BigUint;
+use num_traits::{Pow, ToPrimitive};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+/// Hash functions which may be used to produce a signature.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    /// The DER encoding of the `DigestInfo` structure, minus the digest itself, which identifies
+    /// this hash function inside a signature.
+    pub fn digest_info_prefix(self) -> &'static [u8] {
+        match self {
+            HashAlgorithm::Sha1 => &[
+                0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04,
+                0x14,
+            ],
+            HashAlgorithm::Sha256 => &[
+                0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+                0x01, 0x05, 0x00, 0x04, 0x20,
+            ],
+        }
+    }
+
+    /// Hash `message` with this function.
+    pub fn digest(self, message: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha1 => Sha1::digest(message).to_vec(),
+            HashAlgorithm::Sha256 => Sha256::digest(message).to_vec(),
+        }
+    }
+
+    /// The full DER-encoded `DigestInfo` for `message`.
+    pub fn digest_info(self, message: &[u8]) -> Vec<u8> {
+        let mut info = self.digest_info_prefix().to_vec();
+        info.extend(self.digest(message));
+        info
+    }
+}
+
+/// Reasons a signature may fail to be produced or verified.
+#[derive(Debug, Eq, PartialEq)]
+pub enum SignatureError {
+    /// The modulus is too small to hold the encoded digest.
+    KeyTooShort,
+    /// The signature is not the same length as the modulus.
+    Length,
+    /// The signature does not decrypt to a correctly-formed block.
+    Malformed,
+    /// The signature is well-formed, but for a different message.
+    Mismatch,
+    /// No forgery could be constructed for this key.
+    NoForgery,
+}
+
+/// Produce the encoded message block `00 01 FF .. FF 00 DigestInfo` for a `len`-byte modulus.
+pub fn encode(alg: HashAlgorithm, message: &[u8], len: usize) -> Result<Vec<u8>, SignatureError> {
+    let info = alg.digest_info(message);
+    // at least eight bytes of 0xFF padding are required
+    if len < info.len() + 11 {
+        return Err(SignatureError::KeyTooShort);
+    }
+
+    let mut block = vec![0x00, 0x01];
+    block.extend(vec![0xFF; len - info.len() - 3]);
+    block.push(0x00);
+    block.extend(info);
+    Ok(block)
+}
+
+/// Sign `message` with `key`, hashing it with `alg`.
+pub fn sign(
+    key: &PrivateKey,
+    alg: HashAlgorithm,
+    message: &[u8],
+) -> Result<Vec<u8>, SignatureError> {
+    let block = encode(alg, message, key.size())?;
+    let sig = key.decrypt(&BigUint::from_bytes_be(&block));
+    Ok(to_bytes_padded(&sig, key.size()))
+}
+
+fn recover_block(key: &PublicKey, signature: &[u8]) -> Result<Vec<u8>, SignatureError> {
+    if signature.len() != key.size() {
+        return Err(SignatureError::Length);
+    }
+    let sig = BigUint::from_bytes_be(signature);
+    if sig >= key.n {
+        return Err(SignatureError::Malformed);
+    }
+    Ok(to_bytes_padded(&key.encrypt(&sig), key.size()))
+}
+
+/// Verify `signature` over `message` by re-encoding the expected block and comparing it to the
+/// recovered one in its entirety.
+pub fn verify(
+    key: &PublicKey,
+    alg: HashAlgorithm,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), SignatureError> {
+    let block = recover_block(key, signature)?;
+    let expected = encode(alg, message, key.size())?;
+    if block == expected {
+        Ok(())
+    } else if block.starts_with(&[0x00, 0x01]) {
+        Err(SignatureError::Mismatch)
+    } else {
+        Err(SignatureError::Malformed)
+    }
+}
+
+/// Verify `signature` over `message` the way a careless implementation might: by scanning past
+/// the padding, parsing the `DigestInfo` and digest, and then ignoring whatever follows them.
+///
+/// **Do not use this**; it accepts forgeries. See `forge`.
+pub fn verify_sloppy(
+    key: &PublicKey,
+    alg: HashAlgorithm,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), SignatureError> {
+    let block = recover_block(key, signature)?;
+    if !block.starts_with(&[0x00, 0x01]) {
+        return Err(SignatureError::Malformed);
+    }
+
+    let pad_len = block[2..].iter().take_while(|&&b| b == 0xFF).count();
+    let rest = &block[2 + pad_len..];
+    if pad_len == 0 || rest.first() != Some(&0x00) {
+        return Err(SignatureError::Malformed);
+    }
+
+    let rest = &rest[1..];
+    let prefix = alg.digest_info_prefix();
+    if !rest.starts_with(prefix) {
+        return Err(SignatureError::Malformed);
+    }
+
+    let digest = alg.digest(message);
+    match rest.get(prefix.len()..prefix.len() + digest.len()) {
+        // whatever remains after the digest is never looked at
+        Some(found) if found == digest.as_slice() => Ok(()),
+        Some(_) => Err(SignatureError::Mismatch),
+        None => Err(SignatureError::Malformed),
+    }
+}
+
+/// Forge a signature over `message` which `verify_sloppy` will accept, without the private key.
+///
+/// A block `00 01 FF 00 DigestInfo` is followed by as much garbage as the modulus allows, and the
+/// smallest `e`th root whose power keeps that prefix intact is used as the signature. For small
+/// `e` (i.e. 3) and a reasonably large modulus, the garbage absorbs the rounding error.
+///
+/// With `e = 3` the garbage must cover roughly two thirds of the block, so a 1024-bit key leaves
+/// room for a SHA-1 `DigestInfo` but not a SHA-256 one; those need a 2048-bit modulus. Returns
+/// `NoForgery` when the rounding error would spill into the digest.
+pub fn forge(
+    key: &PublicKey,
+    alg: HashAlgorithm,
+    message: &[u8],
+) -> Result<Vec<u8>, SignatureError> {
+    let len = key.size();
+    let mut prefix = vec![0x00, 0x01, 0xFF, 0x00];
+    prefix.extend(alg.digest_info(message));
+    if prefix.len() >= len {
+        return Err(SignatureError::KeyTooShort);
+    }
+    let garbage_len = len - prefix.len();
+
+    let mut low = prefix.clone();
+    low.extend(vec![0x00; garbage_len]);
+    let mut high = prefix;
+    high.extend(vec![0xFF; garbage_len]);
+    let low = BigUint::from_bytes_be(&low);
+    let high = BigUint::from_bytes_be(&high);
+
+    let e = key
+        .e
+        .to_u32()
+        .filter(|&e| e > 1)
+        .ok_or(SignatureError::NoForgery)?;
+
+    let mut root = low.nth_root(e);
+    if (&root).pow(e) < low {
+        root += 1u32;
+    }
+    if (&root).pow(e) > high {
+        return Err(SignatureError::NoForgery);
+    }
+
+    Ok(to_bytes_padded(&root, len))
+}
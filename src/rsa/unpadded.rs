@@ -0,0 +1,85 @@
+/// Unpadded RSA message recovery.
+///
+/// Textbook RSA is multiplicatively homomorphic: `(s^e * c)^d = s * m (mod n)`. A server which
+/// refuses to decrypt a ciphertext twice can be made to decrypt a blinded copy of it instead, and
+/// the blinding factor divided back out afterwards.
+use super::{PrivateKey, PublicKey};
+use crate::math::{invmod, rand_range};
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+/// Reasons the decryption oracle may refuse a request.
+#[derive(Debug, Eq, PartialEq)]
+pub enum OracleError {
+    /// This ciphertext has been submitted before.
+    Replayed,
+    /// The ciphertext is not smaller than the modulus.
+    OutOfRange,
+}
+
+/// A decryption service which will decrypt any ciphertext exactly once.
+///
+/// Ciphertexts are remembered by their SHA-256 digest, so the cache does not grow with the size
+/// of the modulus.
+pub struct DecryptionOracle {
+    key: PrivateKey,
+    seen: HashSet<Vec<u8>>,
+}
+
+impl DecryptionOracle {
+    /// Create a new oracle which decrypts with `key`.
+    pub fn new(key: PrivateKey) -> Self {
+        DecryptionOracle {
+            key,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Decrypt `c`, provided it has never been seen by this oracle before.
+    pub fn decrypt(&mut self, c: &BigUint) -> Result<BigUint, OracleError> {
+        if c >= &self.key.n {
+            return Err(OracleError::OutOfRange);
+        }
+        let digest = Sha256::digest(&c.to_bytes_be()).to_vec();
+        if !self.seen.insert(digest) {
+            return Err(OracleError::Replayed);
+        }
+        Ok(self.key.decrypt(c))
+    }
+
+    /// The number of distinct ciphertexts decrypted so far.
+    pub fn seen(&self) -> usize {
+        self.seen.len()
+    }
+}
+
+/// Blind `c` under `public` with the factor `s`, yielding `s^e * c mod n`.
+pub fn blind(c: &BigUint, s: &BigUint, public: &PublicKey) -> BigUint {
+    (public.encrypt(s) * c) % &public.n
+}
+
+/// Remove the blinding factor `s` from a decrypted blinded plaintext, yielding `p' / s mod n`.
+///
+/// Returns `None` if `s` is not invertible modulo `n`.
+pub fn unblind(blinded: &BigUint, s: &BigUint, n: &BigUint) -> Option<BigUint> {
+    invmod(s, n).map(|s_inv| (blinded * s_inv) % n)
+}
+
+/// Recover the plaintext of `c` by submitting a randomly-blinded copy of it to `decrypt`.
+pub fn recover_plaintext<F, E>(
+    c: &BigUint,
+    public: &PublicKey,
+    mut decrypt: F,
+) -> Result<BigUint, E>
+where
+    F: FnMut(&BigUint) -> Result<BigUint, E>,
+{
+    let two = BigUint::from(2u32);
+    loop {
+        let s = rand_range(&two, &public.n);
+        if let Some(s_inv) = invmod(&s, &public.n) {
+            return decrypt(&blind(c, &s, public)).map(|p| (p * s_inv) % &public.n);
+        }
+    }
+}
@@ -1,7 +1,11 @@
+use std::cmp::Ordering;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::f64;
+use std::fs;
 use std::hash::Hash;
+use std::io;
+use std::path::Path;
 
 /// A HashMap of `f64`-valued buckets whose chi-squared goodness of fit can be calculated.
 pub struct Histogram<T>(pub HashMap<T, f64>);
@@ -50,6 +54,502 @@ impl Histogram<char> {
             .collect::<HashMap<char, f64>>(),
         )
     }
+
+    /// Letter frequencies for French.
+    pub fn french() -> Self {
+        Histogram(
+            [
+                ('e', 0.14715),
+                ('s', 0.07948),
+                ('a', 0.07636),
+                ('i', 0.07529),
+                ('t', 0.07244),
+                ('n', 0.07095),
+                ('r', 0.06693),
+                ('u', 0.06311),
+                ('o', 0.05796),
+                ('l', 0.05456),
+                ('d', 0.03669),
+                ('c', 0.03260),
+                ('m', 0.02968),
+                ('p', 0.02521),
+                ('v', 0.01838),
+                ('é', 0.01504),
+                ('q', 0.01362),
+                ('f', 0.01066),
+                ('b', 0.00901),
+                ('g', 0.00866),
+                ('h', 0.00737),
+                ('j', 0.00613),
+                ('à', 0.00486),
+                ('x', 0.00427),
+                ('z', 0.00326),
+                ('è', 0.00271),
+                ('ê', 0.00218),
+                ('y', 0.00128),
+                ('ç', 0.00085),
+                ('w', 0.00074),
+                ('û', 0.00060),
+                ('ù', 0.00058),
+                ('â', 0.00051),
+                ('k', 0.00049),
+                ('î', 0.00045),
+                ('ô', 0.00023),
+                ('œ', 0.00018),
+                ('ë', 0.00008),
+                ('ï', 0.00005),
+            ]
+            .iter()
+            .cloned()
+            .collect::<HashMap<char, f64>>(),
+        )
+    }
+
+    /// Letter frequencies for German.
+    pub fn german() -> Self {
+        Histogram(
+            [
+                ('e', 0.16396),
+                ('n', 0.09776),
+                ('s', 0.07270),
+                ('r', 0.07003),
+                ('i', 0.06550),
+                ('a', 0.06516),
+                ('t', 0.06154),
+                ('d', 0.05076),
+                ('h', 0.04577),
+                ('u', 0.04166),
+                ('l', 0.03437),
+                ('g', 0.03009),
+                ('c', 0.02732),
+                ('o', 0.02594),
+                ('m', 0.02534),
+                ('w', 0.01921),
+                ('b', 0.01886),
+                ('f', 0.01656),
+                ('k', 0.01417),
+                ('z', 0.01134),
+                ('ü', 0.00995),
+                ('v', 0.00846),
+                ('p', 0.00670),
+                ('ä', 0.00578),
+                ('ö', 0.00443),
+                ('ß', 0.00307),
+                ('j', 0.00268),
+                ('y', 0.00039),
+                ('x', 0.00034),
+                ('q', 0.00018),
+            ]
+            .iter()
+            .cloned()
+            .collect::<HashMap<char, f64>>(),
+        )
+    }
+
+    /// Letter frequencies for Spanish.
+    pub fn spanish() -> Self {
+        Histogram(
+            [
+                ('e', 0.12181),
+                ('a', 0.11525),
+                ('o', 0.08683),
+                ('s', 0.07977),
+                ('r', 0.06871),
+                ('n', 0.06712),
+                ('i', 0.06247),
+                ('d', 0.05010),
+                ('l', 0.04967),
+                ('t', 0.04632),
+                ('c', 0.04019),
+                ('m', 0.03157),
+                ('u', 0.02927),
+                ('p', 0.02510),
+                ('b', 0.02215),
+                ('g', 0.01768),
+                ('v', 0.01138),
+                ('y', 0.01008),
+                ('q', 0.00877),
+                ('ó', 0.00827),
+                ('í', 0.00725),
+                ('h', 0.00703),
+                ('f', 0.00692),
+                ('á', 0.00502),
+                ('j', 0.00493),
+                ('z', 0.00467),
+                ('é', 0.00433),
+                ('ñ', 0.00311),
+                ('x', 0.00215),
+                ('ú', 0.00168),
+                ('w', 0.00017),
+                ('ü', 0.00012),
+                ('k', 0.00011),
+            ]
+            .iter()
+            .cloned()
+            .collect::<HashMap<char, f64>>(),
+        )
+    }
+
+    /// Letter frequencies for Italian.
+    pub fn italian() -> Self {
+        Histogram(
+            [
+                ('e', 0.11792),
+                ('a', 0.11745),
+                ('i', 0.10143),
+                ('o', 0.09832),
+                ('n', 0.06883),
+                ('l', 0.06510),
+                ('r', 0.06367),
+                ('t', 0.05623),
+                ('s', 0.04981),
+                ('c', 0.04501),
+                ('d', 0.03736),
+                ('p', 0.03056),
+                ('u', 0.03011),
+                ('m', 0.02512),
+                ('v', 0.02097),
+                ('g', 0.01644),
+                ('z', 0.01181),
+                ('f', 0.01153),
+                ('b', 0.00927),
+                ('h', 0.00636),
+                ('à', 0.00635),
+                ('q', 0.00505),
+                ('è', 0.00263),
+                ('ù', 0.00166),
+                ('w', 0.00033),
+                ('ì', 0.00030),
+                ('y', 0.00020),
+                ('j', 0.00011),
+                ('k', 0.00009),
+                ('x', 0.00003),
+                ('ò', 0.00002),
+            ]
+            .iter()
+            .cloned()
+            .collect::<HashMap<char, f64>>(),
+        )
+    }
+
+    /// Letter frequencies for Portuguese.
+    pub fn portuguese() -> Self {
+        Histogram(
+            [
+                ('a', 0.14634),
+                ('e', 0.12570),
+                ('o', 0.09735),
+                ('s', 0.06805),
+                ('r', 0.06530),
+                ('i', 0.06186),
+                ('d', 0.04992),
+                ('m', 0.04738),
+                ('n', 0.04446),
+                ('t', 0.04336),
+                ('c', 0.03882),
+                ('u', 0.03639),
+                ('l', 0.02779),
+                ('p', 0.02523),
+                ('v', 0.01575),
+                ('g', 0.01303),
+                ('q', 0.01204),
+                ('b', 0.01043),
+                ('f', 0.01023),
+                ('h', 0.00781),
+                ('ã', 0.00733),
+                ('ô', 0.00635),
+                ('â', 0.00562),
+                ('ç', 0.00530),
+                ('z', 0.00470),
+                ('ê', 0.00450),
+                ('j', 0.00397),
+                ('é', 0.00337),
+                ('ó', 0.00296),
+                ('x', 0.00253),
+                ('ú', 0.00207),
+                ('í', 0.00132),
+                ('á', 0.00118),
+                ('à', 0.00072),
+                ('õ', 0.00040),
+                ('w', 0.00037),
+                ('ü', 0.00026),
+                ('k', 0.00015),
+                ('y', 0.00006),
+            ]
+            .iter()
+            .cloned()
+            .collect::<HashMap<char, f64>>(),
+        )
+    }
+
+    /// Letter frequencies for Dutch.
+    pub fn dutch() -> Self {
+        Histogram(
+            [
+                ('e', 0.18910),
+                ('n', 0.10032),
+                ('a', 0.07486),
+                ('t', 0.06790),
+                ('i', 0.06499),
+                ('r', 0.06411),
+                ('o', 0.06063),
+                ('d', 0.05933),
+                ('s', 0.03730),
+                ('l', 0.03568),
+                ('g', 0.03403),
+                ('v', 0.02850),
+                ('h', 0.02380),
+                ('k', 0.02248),
+                ('m', 0.02213),
+                ('u', 0.01990),
+                ('b', 0.01584),
+                ('p', 0.01570),
+                ('w', 0.01520),
+                ('j', 0.01460),
+                ('z', 0.01390),
+                ('c', 0.01242),
+                ('f', 0.00805),
+                ('x', 0.00036),
+                ('y', 0.00035),
+                ('q', 0.00009),
+            ]
+            .iter()
+            .cloned()
+            .collect::<HashMap<char, f64>>(),
+        )
+    }
+
+    /// Every built-in language model, with its name.
+    pub fn languages() -> Vec<(&'static str, Self)> {
+        vec![
+            ("english", Histogram::english()),
+            ("french", Histogram::french()),
+            ("german", Histogram::german()),
+            ("spanish", Histogram::spanish()),
+            ("italian", Histogram::italian()),
+            ("portuguese", Histogram::portuguese()),
+            ("dutch", Histogram::dutch()),
+        ]
+    }
+
+    /// The letter frequencies of `text`: its alphabetic characters, lowercased, normalized to sum
+    /// to 1. This is also how to build a language model from a corpus.
+    ///
+    /// ```
+    /// use arse::stat::Histogram;
+    ///
+    /// let letters = Histogram::letters("Abba, 1982!");
+    /// assert_eq!(letters.0.len(), 2);
+    /// assert_eq!(letters.0[&'a'], 0.5);
+    /// ```
+    pub fn letters(text: &str) -> Self {
+        let mut histogram: Histogram<char> = text
+            .chars()
+            .flat_map(char::to_lowercase)
+            .filter(|c| c.is_alphabetic())
+            .into();
+        histogram.normalize();
+        histogram
+    }
+
+    /// Build a language model from the corpus in the file at `path`. Any bytes that aren't UTF-8
+    /// are skipped.
+    ///
+    /// ```
+    /// use arse::stat::Histogram;
+    /// use std::fs;
+    ///
+    /// // challenge 4's lines are hex, so the only letters are a to f
+    /// let model = Histogram::from_corpus_file("tests/data/4.txt").unwrap();
+    /// let text = fs::read_to_string("tests/data/4.txt").unwrap();
+    /// assert_eq!(model.0, Histogram::letters(&text).0);
+    /// assert_eq!(model.0.len(), 6);
+    /// assert!(model.0.keys().all(|c| ('a'..='f').contains(c)));
+    ///
+    /// assert!(Histogram::from_corpus_file("tests/data/missing.txt").is_err());
+    /// ```
+    pub fn from_corpus_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let corpus = fs::read(path)?;
+        Ok(Histogram::letters(&String::from_utf8_lossy(&corpus)))
+    }
+}
+
+/// Guess which of `languages` `text` is written in, by the chi-squared fit of its letter
+/// frequencies against each. Languages whose models lack some letter in the text are ruled out,
+/// and text with no letters at all can't be identified.
+///
+/// ```
+/// use arse::stat::{identify_language, Histogram};
+///
+/// let languages = Histogram::languages();
+/// let text = "Der schnelle braune Fuchs springt über den faulen Hund, \
+///             und die Katze schläft weiter auf dem warmen Sofa.";
+/// assert_eq!(identify_language(text, &languages), Some("german"));
+/// assert_eq!(identify_language("1234 !!", &languages), None);
+/// assert_eq!(identify_language("", &languages), None);
+/// ```
+pub fn identify_language<'a>(
+    text: &str,
+    languages: &'a [(&'a str, Histogram<char>)],
+) -> Option<&'a str> {
+    let letters = Histogram::letters(text);
+    if letters.0.is_empty() {
+        return None;
+    }
+    find_best_in(
+        languages.iter(),
+        |(_, model)| letters.chisq(model).ok(),
+        f64::lt,
+    )
+    .map(|(name, _)| *name)
+}
+
+/// The English text the built-in byte frequencies and n-gram models are counted from: a few
+/// thousand words of public-domain prose.
+const ENGLISH_CORPUS: &str = include_str!("data/english.txt");
+
+/// The add-k smoothing constant for the built-in byte frequencies.
+const BYTE_SMOOTHING: f64 = 0.5;
+
+impl Histogram<u8> {
+    /// The frequencies of all 256 byte values in `corpus`, with add-k smoothing so that the bytes
+    /// it never uses still get a small frequency rather than none. Case, spaces and punctuation
+    /// all count, and nothing has to be UTF-8.
+    pub fn byte_frequencies(corpus: &[u8], smoothing: f64) -> Self {
+        let mut histogram: Histogram<u8> = corpus.iter().cloned().into();
+        for b in 0..=255u8 {
+            *histogram.0.entry(b).or_insert(0f64) += smoothing;
+        }
+        histogram.normalize();
+        histogram
+    }
+
+    /// Byte frequencies for English prose, counted from the built-in corpus.
+    pub fn english_bytes() -> Self {
+        Histogram::byte_frequencies(ENGLISH_CORPUS.as_bytes(), BYTE_SMOOTHING)
+    }
+
+    /// The log-likelihood of `text` per byte under these frequencies. Higher is more plausible; a
+    /// byte with no frequency at all makes it negative infinity. Returns `None` for empty text.
+    ///
+    /// ```
+    /// use arse::stat::Histogram;
+    ///
+    /// let english = Histogram::english_bytes();
+    /// let good = english.log_likelihood(b"Attack at dawn!").unwrap();
+    /// let bad = english.log_likelihood(&[0xff, 0x00, 0x9b, 0x3c, 0x1f]).unwrap();
+    /// assert!(good > bad);
+    /// ```
+    pub fn log_likelihood(&self, text: &[u8]) -> Option<f64> {
+        if text.is_empty() {
+            return None;
+        }
+        let total = text
+            .iter()
+            .map(|b| self.0.get(b).cloned().unwrap_or(0f64).ln())
+            .sum::<f64>();
+        Some(total / text.len() as f64)
+    }
+}
+
+/// The number of distinct symbols `ngram_symbol` maps bytes to: the 69 printable ASCII characters
+/// that aren't upper case letters, and everything else.
+const NGRAM_SYMBOLS: usize = 70;
+
+/// Reduce a byte to the symbol that n-gram models count. Letters are folded to lower case and
+/// whitespace to a space, and the rest of printable ASCII is left alone. Anything else becomes
+/// zero, which doesn't turn up in text.
+fn ngram_symbol(b: u8) -> u8 {
+    match b {
+        b'A'..=b'Z' => b.to_ascii_lowercase(),
+        b'\t' | b'\n' | b'\r' => b' ',
+        b' '..=b'~' => b,
+        _ => 0,
+    }
+}
+
+/// Character n-gram statistics for a language, for scoring candidate plaintexts by
+/// log-likelihood.
+///
+/// Letter frequencies don't say much about a string of a few dozen bytes, but n-grams also know
+/// which symbols follow which, so that "the" scores well and "xq" doesn't. Counts come from a
+/// corpus, and add-k smoothing gives the n-grams it never saw a small probability rather than
+/// none at all.
+///
+/// ```
+/// use arse::stat::NgramModel;
+///
+/// let trigrams = NgramModel::english(3);
+/// let good = trigrams.log_likelihood(b"Attack at dawn").unwrap();
+/// let bad = trigrams.log_likelihood(b"Xtqzkc vj fqwp").unwrap();
+/// assert!(good > bad);
+/// ```
+pub struct NgramModel {
+    n: usize,
+    smoothing: f64,
+    ngrams: HashMap<Vec<u8>, f64>,
+    contexts: HashMap<Vec<u8>, f64>,
+}
+
+impl NgramModel {
+    /// The add-k smoothing constant the built-in models use.
+    pub const DEFAULT_SMOOTHING: f64 = 0.5;
+
+    /// Count the `n`-grams of `corpus`, smoothing with `smoothing` added to every count.
+    pub fn from_corpus(corpus: &[u8], n: usize, smoothing: f64) -> Self {
+        assert!(n > 0, "n-grams must be at least one symbol long");
+        let symbols = corpus.iter().cloned().map(ngram_symbol).collect::<Vec<_>>();
+        let mut ngrams = HashMap::new();
+        let mut contexts = HashMap::new();
+        for window in symbols.windows(n) {
+            *ngrams.entry(window.to_vec()).or_insert(0f64) += 1f64;
+            *contexts.entry(window[..n - 1].to_vec()).or_insert(0f64) += 1f64;
+        }
+        NgramModel {
+            n,
+            smoothing,
+            ngrams,
+            contexts,
+        }
+    }
+
+    /// Count the `n`-grams of the corpus in the file at `path`.
+    pub fn from_corpus_file<P: AsRef<Path>>(path: P, n: usize, smoothing: f64) -> io::Result<Self> {
+        Ok(NgramModel::from_corpus(&fs::read(path)?, n, smoothing))
+    }
+
+    /// A model of English `n`-grams, counted from a built-in corpus.
+    pub fn english(n: usize) -> Self {
+        NgramModel::from_corpus(ENGLISH_CORPUS.as_bytes(), n, NgramModel::DEFAULT_SMOOTHING)
+    }
+
+    /// The length of the n-grams this model counts.
+    pub fn order(&self) -> usize {
+        self.n
+    }
+
+    /// The smoothed log-probability of the last symbol of `ngram` following the ones before it.
+    fn log_prob(&self, ngram: &[u8]) -> f64 {
+        let count = self.ngrams.get(ngram).cloned().unwrap_or(0f64);
+        let context = self
+            .contexts
+            .get(&ngram[..self.n - 1])
+            .cloned()
+            .unwrap_or(0f64);
+        ((count + self.smoothing) / (context + self.smoothing * NGRAM_SYMBOLS as f64)).ln()
+    }
+
+    /// The log-likelihood of `text` per n-gram, so that texts of different lengths compare
+    /// fairly. Higher is more plausible. Returns `None` if `text` is shorter than one n-gram.
+    pub fn log_likelihood(&self, text: &[u8]) -> Option<f64> {
+        let symbols = text.iter().cloned().map(ngram_symbol).collect::<Vec<_>>();
+        let windows = symbols.len().checked_sub(self.n - 1).filter(|&w| w > 0)?;
+        let total = symbols
+            .windows(self.n)
+            .map(|ngram| self.log_prob(ngram))
+            .sum::<f64>();
+        Some(total / windows as f64)
+    }
 }
 
 impl<T> Histogram<T>
@@ -69,10 +569,33 @@ where
             .map(|v| v.iter().sum())
     }
 
+    /// Add one to the bucket for `item`, creating it if needed.
+    pub fn insert(&mut self, item: T) {
+        *self.0.entry(item).or_insert(0f64) += 1f64;
+    }
+
+    /// The item whose bucket holds the largest value, if there are any buckets at all.
+    pub fn most_common(&self) -> Option<&T> {
+        self.0
+            .iter()
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+            .map(|(item, _)| item)
+    }
+
     /// Normalize this histogram, making all values sum to 1.
+    ///
+    /// ```
+    /// use arse::stat::Histogram;
+    ///
+    /// let mut histogram = Histogram::from("aabc".chars());
+    /// histogram.normalize();
+    /// assert_eq!(histogram.0[&'a'], 0.5);
+    /// assert_eq!(histogram.0[&'b'], 0.25);
+    /// assert_eq!(histogram.0.values().sum::<f64>(), 1.0);
+    /// ```
     pub fn normalize(&mut self) {
-        let len = self.0.iter().len();
-        self.0.iter_mut().for_each(|(_, n)| *n = *n / (len as f64));
+        let total = self.0.values().sum::<f64>();
+        self.0.iter_mut().for_each(|(_, n)| *n /= total);
     }
 }
 
@@ -106,3 +106,26 @@ pub fn pkcs7_pad(mut input: Vec<u8>, len: usize) -> Result<Vec<u8>, Pkcs7PadErr>
     input.append(&mut vec![pad_len as u8; pad_len]);
     Ok(input)
 }
+
+/// Pad the input with PKCS#7 padding up to the next multiple of `block_size`. A full block of
+/// padding is added if the input is already block-aligned.
+///
+/// ```
+/// use arse::transform::pkcs7_pad_blocks;
+///
+/// assert_eq!(
+///     pkcs7_pad_blocks(b"YELLOW".to_vec(), 8).unwrap(),
+///     b"YELLOW\x02\x02".to_vec()
+///     );
+/// assert_eq!(pkcs7_pad_blocks(b"SUBMARINE".to_vec(), 9).unwrap().len(), 18);
+/// assert!(pkcs7_pad_blocks(b"too big".to_vec(), 256).is_err());
+/// ```
+pub fn pkcs7_pad_blocks(mut input: Vec<u8>, block_size: usize) -> Result<Vec<u8>, Pkcs7PadErr> {
+    if block_size == 0 || block_size > usize::from(u8::MAX) {
+        return Err(Pkcs7PadErr);
+    }
+
+    let pad_len = block_size - input.len() % block_size;
+    input.append(&mut vec![pad_len as u8; pad_len]);
+    Ok(input)
+}
@@ -106,6 +106,33 @@ fn challenge3() {
     println!("Using value {:X}, decoded string: {}", best_byte, cleartext);
 }
 
+/// Challenge 3 again, scoring with n-gram models instead of letter frequencies, on the whole
+/// ciphertext and on just its first eight bytes.
+#[test]
+fn challenge3_ngram() {
+    use arse::encode::hex::TryFromHex;
+    use arse::stat::{find_best_in, NgramModel};
+    use arse::xor_cipher::score_byte_decode;
+    use std::f64;
+
+    const CIPHERTEXT: &str = "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736";
+
+    let ctext_bytes = CIPHERTEXT.try_from_hex().unwrap();
+
+    for n in 2..=3 {
+        let model = NgramModel::english(n);
+        for ctext in &[&ctext_bytes[..], &ctext_bytes[..8]] {
+            let best_byte = find_best_in(
+                0..=255u8,
+                |&test_byte| score_byte_decode(test_byte, ctext, &model).ok(),
+                f64::lt,
+            )
+            .unwrap();
+            assert_eq!(best_byte, b'X', "{}-gram on {} bytes", n, ctext.len());
+        }
+    }
+}
+
 /// # Detect single-character XOR
 ///
 /// One of the 60-character strings in [this file](data/4.txt) has been encrypted by single-character XOR.
@@ -0,0 +1,613 @@
+/// # Implement unpadded message recovery oracle
+///
+/// Nate Lawson says we should stop calling it "RSA padding" and start calling it "RSA armoring".
+/// Here's why.
+///
+/// Imagine a web application, again with the Javascript encryption, taking RSA-encrypted messages
+/// which (again: Javascript) aren't padded before encryption at all.
+///
+/// You can submit an arbitrary RSA blob and the server will return plaintext. But you can't submit
+/// the same message twice: let's say the server keeps hashes of previous messages for some
+/// liveness interval, and that the message has an embedded timestamp.
+///
+/// You capture other users' messages and would like to decrypt them. But when you try to submit
+/// them to the server, they get rejected.
+///
+/// Let's implement that server.
+///
+/// Capture the ciphertext C, and:
+///
+/// Let N and E be the public modulus and exponent respectively.
+///
+/// Let S be a random number > 1 mod N. Doesn't matter what.
+///
+/// Now:
+///
+/// ```text
+/// C' = ((S**E mod N) C) mod N
+/// ```
+///
+/// Submit C', which appears totally different from C, to the server, recovering P', which appears
+/// totally different from P
+///
+/// Now:
+///
+/// ```text
+///           P'
+///     P = -----  mod N
+///           S
+/// ```
+///
+/// Oops!
+///
+/// Remember: you don't simply divide mod N; you multiply by the multiplicative inverse mod N.
+/// So you'll need a modinv() function.
+#[test]
+fn challenge41() {
+    use arse::rsa::generate_keypair;
+    use arse::rsa::unpadded::{recover_plaintext, DecryptionOracle, OracleError};
+    use num_bigint::BigUint;
+
+    let (public, private) = generate_keypair(1024, &BigUint::from(3u32));
+    let mut oracle = DecryptionOracle::new(private);
+
+    let secret = BigUint::from_bytes_be(b"{time: 1356304276, social: '555-55-5555'}");
+    let ciphertext = public.encrypt(&secret);
+
+    // the victim's own request goes through...
+    assert_eq!(oracle.decrypt(&ciphertext).unwrap(), secret);
+    // ...but a replay of it is refused
+    assert_eq!(oracle.decrypt(&ciphertext), Err(OracleError::Replayed));
+
+    let recovered = recover_plaintext(&ciphertext, &public, |c| oracle.decrypt(c)).unwrap();
+    assert_eq!(recovered, secret);
+    assert_eq!(oracle.seen(), 2);
+}
+
+/// # Bleichenbacher's e=3 RSA Attack
+///
+/// > Crypto-tourism informational placard.
+/// >
+/// > This attack broke Firefox's TLS certificate validation several years ago. You could write a
+/// > Python script to fake an RSA signature for any certificate. We find new instances of it
+/// > every other year or so.
+///
+/// RSA with an encrypting exponent of 3 is popular, because it makes the RSA math faster.
+///
+/// With e=3 RSA, encryption is just cubing a number mod the public encryption modulus:
+///
+/// ```text
+///  c = m ** 3 % n
+/// ```
+///
+/// e=3 is secure as long as we can make assumptions about the message blocks we're encrypting.
+/// The worry with low-exponent RSA is that the message blocks we process won't be large enough
+/// to wrap the modulus after being cubed. The block 00:02 (imagine sufficient zero-padding)
+/// can be "encrypted" in e=3 RSA; it is simply 00:08.
+///
+/// When RSA is used to sign, rather than encrypt, the operations are reversed; the verifier
+/// "decrypts" the message by cubing it. This produces a "plaintext" which the verifier checks for
+/// validity.
+///
+/// When you RSA-sign a message, you take a hash of the message, pad it to the size of the
+/// modulus, and encrypt it with the private key. The padding is PKCS#1 v1.5:
+///
+/// ```text
+/// 00h 01h ffh ffh ... ffh ffh 00h ASN.1 GOOP HASH
+/// ```
+///
+/// As intended, the ffh bytes in that block expand to fill the whole block, producing a
+/// "right-justified" hash (the last byte of the hash is the last byte of the message).
+///
+/// There was, 7 years ago, a common implementation flaw with RSA verifiers: they'd verify
+/// signatures by "decrypting" them (cubing them modulo the public exponent) and then "parsing"
+/// them by looking for 00h 01h ... ffh 00h ASN.1 HASH.
+///
+/// This is a bug because it implies the verifier isn't checking all the padding. If you don't
+/// check the padding, you leave open the possibility that instead of hundreds of ffh bytes, you
+/// have only a few, which if you think about it means there could be squizzilions of possible
+/// numbers that could produce a valid-looking signature.
+///
+/// How to find such a block? Find a number that when cubed (a) doesn't wrap the modulus (thus
+/// bypassing the key entirely) and (b) produces a block that starts "00h 01h ffh ... 00h ASN.1
+/// HASH".
+///
+/// There are two ways to approach this problem:
+///
+/// * You can work from Hal Finney's writeup, available on Google, of how Bleichenbacher explained
+///   the math "so that you can do it by hand with a pencil".
+/// * You can implement an integer cube root in your language, format the message block you want
+///   to forge, leaving sufficient trailing zeros at the end to fill with garbage, then take the
+///   cube-root of that block.
+///
+/// Forge a 1024-bit RSA signature for the string "hi mom". Make sure your implementation actually
+/// accepts the signature!
+#[test]
+fn challenge42() {
+    use arse::rsa::generate_keypair;
+    use arse::rsa::signature::{forge, sign, verify, verify_sloppy, HashAlgorithm, SignatureError};
+    use num_bigint::BigUint;
+
+    let (public, private) = generate_keypair(1024, &BigUint::from(3u32));
+
+    for &alg in &[HashAlgorithm::Sha1, HashAlgorithm::Sha256] {
+        // an honest signature passes both verifiers
+        let sig = sign(&private, alg, b"hi dad").unwrap();
+        assert_eq!(verify(&public, alg, b"hi dad", &sig), Ok(()));
+        assert_eq!(verify_sloppy(&public, alg, b"hi dad", &sig), Ok(()));
+        assert_eq!(
+            verify(&public, alg, b"hi mom", &sig),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    // a forged signature only fools the sloppy verifier
+    let forged = forge(&public, HashAlgorithm::Sha1, b"hi mom").unwrap();
+    assert_eq!(
+        verify_sloppy(&public, HashAlgorithm::Sha1, b"hi mom", &forged),
+        Ok(())
+    );
+    assert!(verify(&public, HashAlgorithm::Sha1, b"hi mom", &forged).is_err());
+
+    // a SHA-256 DigestInfo leaves too little room for garbage in a 1024-bit block
+    assert_eq!(
+        forge(&public, HashAlgorithm::Sha256, b"hi mom"),
+        Err(SignatureError::NoForgery)
+    );
+}
+
+/// # DSA key recovery from nonce
+///
+/// Step 1: Relocate so that you are out of easy travel distance of us.
+///
+/// Step 2: Implement DSA, up to signing and verifying, including parameter generation.
+///
+/// Hah-hah you're too far away to come punch us.
+///
+/// Just kidding you can skip the parameter generation part if you want; if you do, use these
+/// params (the ones in `dsa::Params::cryptopals()`).
+///
+/// ("But I want smaller params!" Then generate them yourself.)
+///
+/// The DSA signing operation generates a random subkey "k". You know this because you implemented
+/// the DSA sign operation.
+///
+/// This is the first and easier of two challenges regarding the DSA "k" subkey.
+///
+/// Given a known "k", it's trivial to recover the DSA private key "x":
+///
+/// ```text
+///           (s * k) - H(msg)
+///       x = ----------------  mod q
+///                   r
+/// ```
+///
+/// Do this a couple times to prove to yourself that you grok it. Capture it in a function of
+/// some sort.
+///
+/// Now then. I used the parameters above. I generated a keypair. My pubkey is `y` below.
+///
+/// I signed
+///
+/// > For those that envy a MC it can be hazardous to your health
+/// > So be friendly, a matter of life and death, just like a etch-a-sketch
+///
+/// (My SHA1 for this string was d2d0714f014a9784047eaeccf956520045c45265; I don't know what NIST
+/// wants you to do, but when I convert that hash to an integer I get
+/// 0xd2d0714f014a9784047eaeccf956520045c45265).
+///
+/// I get the signature `r` and `s` below.
+///
+/// I signed this string with a broken implemention of DSA that generated "k" values between 0
+/// and 2^16. What's my private key?
+///
+/// Its SHA-1 fingerprint (after being converted to hex) is:
+///
+/// `0954edd5e0afe5542a4adf012611a91912a3ec16`
+///
+/// Obviously, it also generates the same signature for that string.
+#[test]
+fn challenge43() {
+    use arse::dsa::{
+        generate_keypair, hash_message, recover_from_weak_nonce, x_from_nonce, Params, PublicKey,
+        Signature,
+    };
+    use arse::encode::hex::ToHex;
+    use num_bigint::BigUint;
+    use num_traits::Num;
+    use sha1::{Digest, Sha1};
+
+    let params = Params::cryptopals();
+
+    // sanity check signing and verification, and key recovery from a known nonce
+    let (public, private) = generate_keypair(&params);
+    let sig = private.sign(b"hello, world");
+    assert!(public.verify(b"hello, world", &sig));
+    assert!(!public.verify(b"goodbye, world", &sig));
+
+    let h = hash_message(b"hello, world");
+    let k = BigUint::from(12345u32);
+    let sig = private.sign_with_nonce(&h, &k).unwrap();
+    assert_eq!(x_from_nonce(&params, &h, &sig, &k), Some(private.x.clone()));
+
+    let public = PublicKey {
+        params,
+        y: BigUint::from_str_radix(
+            "84ad4719d044495496a3201c8ff484feb45b962e7302e56a392aee4\
+             abab3e4bdebf2955b4736012f21a08084056b19bcd7fee56048e004\
+             e44984e2f411788efdc837a0d2e5abb7b555039fd243ac01f0fb2ed\
+             1dec568280ce678e931868d23eb095fde9d3779191b8c0299d6e07b\
+             bb283e6633451e535c45513b2d33c99ea17",
+            16,
+        )
+        .unwrap(),
+    };
+    let msg = b"For those that envy a MC it can be hazardous to your health\n\
+                So be friendly, a matter of life and death, just like a etch-a-sketch\n";
+    let h = hash_message(msg);
+    assert_eq!(
+        h.to_str_radix(16),
+        "d2d0714f014a9784047eaeccf956520045c45265"
+    );
+
+    let sig = Signature {
+        r: "548099063082341131477253921760299949438196259240"
+            .parse()
+            .unwrap(),
+        s: "857042759984254168557880549501802188789837994940"
+            .parse()
+            .unwrap(),
+    };
+    assert!(public.verify(msg, &sig));
+
+    let private = recover_from_weak_nonce(&public, &h, &sig, 1 << 16).unwrap();
+    let fingerprint = Sha1::digest(private.x.to_str_radix(16).as_bytes()).to_hex();
+    assert_eq!(fingerprint, "0954edd5e0afe5542a4adf012611a91912a3ec16");
+}
+
+/// # DSA nonce recovery from repeated nonce
+///
+/// > Cryptanalytic MVP award.
+/// >
+/// > This attack (in an elliptic curve group) broke the PS3. It is a great, great attack.
+///
+/// [In this file](data/44.txt) find a collection of DSA-signed messages. (NB: each msg has a
+/// trailing space.)
+///
+/// These were signed under the following pubkey (`y` below), using the same domain parameters as
+/// the previous exercise.
+///
+/// It should not be hard to find the messages for which we have accidentally used a repeated "k".
+/// Given a pair of such messages, you can discover the "k" we used with the following formula:
+///
+/// ```text
+///           (m1 - m2)
+///       k = --------- mod q
+///           (s1 - s2)
+/// ```
+///
+/// What's my private key? (The data file here was signed with our own key rather than the one in
+/// the original challenge, so we check the recovered key against `y` instead of a fingerprint.)
+#[test]
+fn challenge44() {
+    use arse::dsa::{
+        find_repeated_nonces, hash_message, parse_signed_messages, recover_from_repeated_nonce,
+        recover_repeated_nonce, Params, PublicKey,
+    };
+    use num_bigint::BigUint;
+    use num_traits::Num;
+
+    let public = PublicKey {
+        params: Params::cryptopals(),
+        y: BigUint::from_str_radix(
+            "5a6775b9e702bc7aa50eaab52b38a47184bc326a90b5868a13cfbc9\
+             f059115c581084ba629ab77fb1729d58c5e0deb701883ea42259ffe\
+             590070bf4e978a8460fdd07f159b8ab2e9938e958737f15d5d51b8f\
+             25f43b8556f558df896dc60fd7d49e7559ed7dbddfe84418d4cccf8\
+             6a0ed0dc5106105094ff1faa91978214270c",
+            16,
+        )
+        .unwrap(),
+    };
+
+    let messages = parse_signed_messages(include_str!("data/44.txt")).unwrap();
+    assert_eq!(messages.len(), 11);
+    for m in messages.iter() {
+        assert_eq!(hash_message(m.msg.as_bytes()), m.m);
+        assert!(public.verify(m.msg.as_bytes(), &m.sig));
+    }
+
+    assert_eq!(
+        find_repeated_nonces(&messages),
+        vec![(0, 6), (1, 3), (5, 10)]
+    );
+
+    // a signature parsed with an unreduced s gives the same nonce
+    let k = recover_repeated_nonce(&public.params, &messages[1], &messages[3]).unwrap();
+    let mut unreduced = messages[3].clone();
+    unreduced.sig.s += &public.params.q * 2u32;
+    assert_eq!(
+        recover_repeated_nonce(&public.params, &messages[1], &unreduced),
+        Some(k)
+    );
+
+    let private = recover_from_repeated_nonce(&public, &messages).unwrap();
+    assert_eq!(private.public_key(), public);
+}
+
+/// # DSA parameter tampering
+///
+/// Take your DSA code from the previous exercise. Imagine it as part of an algorithm in which
+/// the client was allowed to propose domain parameters (the p and q moduli, and the g
+/// generator).
+///
+/// This would be bad, because attackers could trick victims into accepting bad parameters.
+/// Vaudenay gave two examples of bad generator parameters: generators that were 0 mod p, and
+/// generators that were 1 mod p.
+///
+/// Use the parameters from the previous exercise, but substitute 0 for "g". Generate a signature.
+/// You will notice something bad. Verify the signature. Now verify any other signature, for any
+/// other string.
+///
+/// Now, try (p+1) as "g". With this "g", you can generate a magic signature s, r for any DSA
+/// public key that will validate against any string. For arbitrary z:
+///
+/// ```text
+///   r = ((y**z) % p) % q
+///
+///         r
+///   s =  --- % q
+///         z
+/// ```
+///
+/// Sign "Hello, world". And "Goodbye, world".
+#[test]
+fn challenge45() {
+    use arse::dsa::{generate_keypair, magic_signature, Params, Signature, Strictness};
+    use num_bigint::BigUint;
+    use num_traits::{One, Zero};
+
+    let params = Params::cryptopals();
+
+    // g = 0: every signature has r = 0, and a lax verifier accepts any such signature for any
+    // message
+    let (public, private) = generate_keypair(&params.with_generator(BigUint::zero()));
+    assert_eq!(private.sign_with(b"Hello, world", Strictness::Strict), None);
+    let sig = private.sign_with(b"Hello, world", Strictness::Lax).unwrap();
+    assert!(sig.r.is_zero());
+    let anything = Signature {
+        r: BigUint::zero(),
+        s: BigUint::from(31337u32),
+    };
+    for msg in &[&b"Hello, world"[..], b"Goodbye, world"] {
+        assert!(public.verify_with(msg, &sig, Strictness::Lax));
+        assert!(public.verify_with(msg, &anything, Strictness::Lax));
+        assert!(!public.verify(msg, &sig));
+        assert!(!public.verify(msg, &anything));
+    }
+
+    // g = p + 1: the magic signature validates against any string
+    let (public, private) = generate_keypair(&params.with_generator(&params.p + BigUint::one()));
+    assert_eq!(private.sign_with(b"Hello, world", Strictness::Strict), None);
+    let magic = magic_signature(&public, &BigUint::from(42u32)).unwrap();
+    for msg in &[&b"Hello, world"[..], b"Goodbye, world"] {
+        assert!(public.verify_with(msg, &magic, Strictness::Lax));
+        assert!(!public.verify(msg, &magic));
+    }
+}
+
+/// # RSA parity oracle
+///
+/// > When does this ever happen?
+/// >
+/// > This is a bit of a toy problem, but it's very helpful for understanding what RSA is doing
+/// > (and also for why pure number-theoretic encryption is terrifying). Trust us, you want to do
+/// > this before trying the next challenge. Also, it's fun.
+///
+/// Generate a 1024 bit RSA key pair.
+///
+/// Write an oracle function that uses the private key to answer the question "is the plaintext of
+/// this message even or odd" (is the last bit of the message 0 or 1). Imagine for instance a
+/// server that accepted RSA-encrypted messages and checked the parity of their decryption to
+/// validate them, and spat out an error if they were of the wrong parity.
+///
+/// Anyways: function returning true or false based on whether the decrypted plaintext was even or
+/// odd, and nothing else.
+///
+/// Take the following string and un-Base64 it in your code (without looking at it!) and encrypt
+/// it to the public key, creating a ciphertext:
+///
+/// `VGhhdCdzIHdoeSBJIGZvdW5kIHlvdSBkb24ndCBwbGF5IGFyb3VuZCB3aXRoIHRoZSBGdW5reSBDb2xkIE1lZGluYQ==`
+///
+/// With your oracle function, you can trivially decrypt the message.
+///
+/// Here's why:
+///
+/// * RSA ciphertexts are just numbers. You can do trivial math on them. You can for instance
+///   multiply a ciphertext by the RSA-encryption of another number; the corresponding plaintext
+///   will be the product of those two numbers.
+/// * If you double a ciphertext (multiply it by (2**e)%n), the resulting plaintext will (obviously)
+///   be either even or odd.
+/// * If the plaintext after doubling is even, doubling the plaintext didn't wrap the modulus ---
+///   the modulus is a prime number. That means the plaintext is less than half the modulus.
+///
+/// You can repeatedly apply this heuristic, once per bit of the message, checking your oracle
+/// function each time.
+///
+/// Your decryption function starts with bounds for the plaintext of [0,n].
+///
+/// Each iteration of your decryption cuts the bounds in half; either the upper bound is reduced
+/// by half, or the lower bound is.
+///
+/// After log2(n) iterations, you have the decryption of the message.
+///
+/// Print the upper bound of the message as a string at each iteration; you'll see the message
+/// decrypt "hollywood style".
+///
+/// Decrypt the string (after encrypting it to a hidden private key) above.
+#[test]
+fn challenge46() {
+    use arse::encode::base64::TryFromBase64;
+    use arse::rsa::generate_keypair;
+    use arse::rsa::parity::{decrypt, ParityOracle};
+    use num_bigint::BigUint;
+
+    let (public, private) = generate_keypair(1024, &BigUint::from(65537u32));
+    let oracle = ParityOracle::new(private);
+
+    let secret = "VGhhdCdzIHdoeSBJIGZvdW5kIHlvdSBkb24ndCBwbGF5IGFyb3VuZCB3aXRoIHRoZSBGdW5reSBDb2xkIE1lZGluYQ=="
+        .try_from_base64()
+        .unwrap();
+    let ciphertext = public.encrypt(&BigUint::from_bytes_be(&secret));
+
+    let mut steps = 0;
+    let mut last_bound = public.n.clone();
+    let plaintext = decrypt(
+        &ciphertext,
+        &public,
+        |c| oracle.is_even(c),
+        |upper| {
+            // the upper bound only ever shrinks
+            assert!(upper <= &last_bound);
+            last_bound = upper.clone();
+            steps += 1;
+        },
+    );
+
+    assert_eq!(steps, 1024);
+    assert_eq!(plaintext.to_bytes_be(), secret);
+}
+
+/// # Bleichenbacher's PKCS 1.5 Padding Oracle (Simple Case)
+///
+/// > Degree of difficulty: Hard
+/// >
+/// > This is a bit hard. It's a famous attack, and the original paper is very readable.
+///
+/// Let us Google this for you: "Chosen ciphertext attacks against protocols based on the RSA
+/// encryption standard".
+///
+/// This is Bleichenbacher from CRYPTO '98; I get a bunch of .ps versions on the first search page.
+///
+/// Read the paper. It describes a padding oracle attack on PKCS#1v1.5. The attack is similar in
+/// spirit to the CBC padding oracle you built earlier; it's an "adaptive chosen ciphertext
+/// attack", which means you start with a valid ciphertext and repeatedly corrupt it, bouncing the
+/// adulterated ciphertexts off the target to learn things about the original.
+///
+/// This is a common flaw even in modern cryptosystems that use RSA.
+///
+/// It's also the most fun you can have building a crypto attack. It involves 9th grade math, but
+/// also has you implementing an algorithm that is complex on par with finding a minimum cost
+/// spanning tree.
+///
+/// The setup:
+///
+/// * Build an oracle function, just like you did in the last exercise, but have it check for
+///   plaintext[0] == 0 and plaintext[1] == 2.
+/// * Generate a 256 bit keypair (that is, p and q will each be 128 bit primes), [n, e, d].
+/// * Plug d and n into your oracle function.
+/// * PKCS1.5-pad a short message, like "kick it, CC", and call it "m". Encrypt to to get "c".
+/// * Decrypt "c" using your padding oracle.
+///
+/// For this challenge, we've used an untypically small RSA modulus, because it makes the attack
+/// converge faster. We'll do a larger one in the next challenge. Do all the steps in the paper;
+/// you'll just find that some of them (like multiple-interval narrowing) don't come into play
+/// much.
+#[test]
+fn challenge47() {
+    use arse::rsa::bleichenbacher::{Attack, PaddingOracle, Progress};
+    use arse::rsa::pkcs1::{pad, unpad};
+    use arse::rsa::{generate_keypair, to_bytes_padded};
+    use num_bigint::BigUint;
+
+    let (public, private) = generate_keypair(256, &BigUint::from(3u32));
+    let oracle = PaddingOracle::new(private);
+
+    let m = pad(b"kick it, CC", public.size()).unwrap();
+    let c = public.encrypt(&BigUint::from_bytes_be(&m));
+    assert!(oracle.is_conforming(&c));
+
+    // run the attack for a little while, then save it...
+    let mut attack = Attack::new(&public, &c, |c| oracle.is_conforming(c));
+    let saved = match attack.run(|c| oracle.is_conforming(c), Some(100)) {
+        Progress::Interrupted => attack.to_string(),
+        Progress::Done(_) => panic!("attack finished implausibly quickly"),
+    };
+    assert_eq!(attack.calls(), 101);
+
+    // ...and pick it back up from where it left off
+    let mut resumed: Attack = saved.parse().unwrap();
+    assert_eq!(resumed, attack);
+    let recovered = match resumed.run(|c| oracle.is_conforming(c), None) {
+        Progress::Done(m) => m,
+        Progress::Interrupted => unreachable!(),
+    };
+    assert!(resumed.calls() > attack.calls());
+
+    let recovered = to_bytes_padded(&recovered, public.size());
+    assert_eq!(recovered, m);
+    assert_eq!(unpad(&recovered).unwrap(), b"kick it, CC");
+}
+
+/// # Bleichenbacher's PKCS 1.5 Padding Oracle (Complete Case)
+///
+/// > Cryptocalypse
+/// >
+/// > This is an extraordinarily useful attack. PKCS#1v15 padding, despite being totally insecure,
+/// > is the default padding used by RSA implementations. The OAEP standard that replaces it is not
+/// > widely implemented. This attack routinely breaks SSL/TLS.
+///
+/// This is a continuation of challenge #47; it implements the complete BB'98 attack.
+///
+/// Set yourself up the way you did in #47, but this time generate a 768 bit modulus.
+///
+/// To make the attack work with a realistic RSA keypair, you need to reproduce step 2b from the
+/// paper, and your implementation of Step 3 needs to handle multiple ranges.
+///
+/// The full Bleichenbacher attack works basically like this:
+///
+/// * Starting from the smallest 's' that could possibly produce a plaintext bigger than 2B,
+///   iteratively search for an 's' that produces a conformant plaintext.
+/// * For our known 's1' and 'n', solve m1=m0s1-rn (again: just a definition of modular
+///   multiplication) for 'r', the number of times we've wrapped the modulus.
+/// * 'm0' and 'm1' are unknowns, but we know both are conformant PKCS#1v1.5 plaintexts, and so are
+///   between [2B,3B].
+/// * We substitute the known bounds for both, leaving only 'r' free, and solve for a range of
+///   possible 'r' values. This range should be small!
+/// * Solve m1=m0s1-rn again but this time for 'm0', plugging in each value of 'r' we generated in
+///   the last step. This gives us new intervals to work with. Rule out any interval that is
+///   outside 2B,3B.
+/// * Repeat the process for successively higher values of 's'. Eventually, this process will get
+///   us down to just one interval, whereupon we're back to exercise #47.
+///
+/// What happens when we get down to one interval is, we stop blindly incrementing 's'; instead,
+/// we start rapidly growing 'r' and backing it out to 's' values by solving m1=m0s1-rn for 's'
+/// instead of 'r' or 'm0'. So much algebra! Make your teenage son do it for you! *Note: does not
+/// work well in practice*
+#[test]
+fn challenge48() {
+    use arse::rsa::bleichenbacher::{Attack, PaddingOracle, Progress};
+    use arse::rsa::pkcs1::{pad, unpad};
+    use arse::rsa::{generate_keypair, to_bytes_padded};
+    use num_bigint::BigUint;
+
+    let (public, private) = generate_keypair(768, &BigUint::from(3u32));
+    let oracle = PaddingOracle::new(private);
+
+    let m = pad(b"kick it, CC", public.size()).unwrap();
+    let c = public.encrypt(&BigUint::from_bytes_be(&m));
+
+    let mut attack = Attack::new(&public, &c, |c| oracle.is_conforming(c));
+    let recovered = match attack.run(|c| oracle.is_conforming(c), None) {
+        Progress::Done(m) => m,
+        Progress::Interrupted => unreachable!(),
+    };
+    println!(
+        "recovered plaintext in {} iterations with {} oracle calls",
+        attack.iterations(),
+        attack.calls()
+    );
+
+    let recovered = to_bytes_padded(&recovered, public.size());
+    assert_eq!(unpad(&recovered).unwrap(), b"kick it, CC");
+}
@@ -0,0 +1,732 @@
+/// # CBC-MAC Message Forgery
+///
+/// Let's talk about CBC-MAC.
+///
+/// CBC-MAC is like this:
+///
+/// 1. Take the plaintext P.
+/// 2. Encrypt P under CBC with key K, yielding ciphertext C.
+/// 3. Chuck all of C but the last block C\[n\].
+/// 4. C\[n\] is the MAC.
+///
+/// Suppose there's an online banking application, and it carries out user requests by talking to
+/// an API server over the network. Each request looks like this:
+///
+/// ```text
+/// message || IV || MAC
+/// ```
+///
+/// The message looks like this:
+///
+/// ```text
+/// from=#{from_id}&to=#{to_id}&amount=#{amount}
+/// ```
+///
+/// Now, write an API server and a web frontend for it. (NOTE: No need to get ambitious and write
+/// actual servers and web apps. Totally fine to go lo-fi on this one.) The client and server
+/// should share a secret key K to sign and verify messages.
+///
+/// The API server should accept messages, verify signatures, and carry out each transaction if
+/// the MAC is valid. It's also publicly exposed - the attacker can submit messages freely
+/// assuming he can forge the right MAC.
+///
+/// The web client should allow the attacker to generate valid messages for accounts he controls.
+/// (Feel free to sanity check that it's impossible to generate valid messages for other users'
+/// accounts.)
+///
+/// Your mission: capture a valid message for your target user. Use length extension to add a
+/// transaction paying the attacker's account 1M spacebucks.
+///
+/// > Hint!
+/// >
+/// > This would be a lot easier if you had full control over the first block of your message,
+/// > huh? Maybe you can simulate that.
+///
+/// Food for thought: How would you modify the protocol to prevent this?
+///
+/// Now let's tune up that protocol a little bit.
+///
+/// As we now know, you're supposed to use a fixed IV with CBC-MAC, so let's do that. We'll set ours
+/// at 0 for simplicity. This means the IV comes out of the protocol:
+///
+/// ```text
+/// message || MAC
+/// ```
+///
+/// Pretty simple, but we'll also adjust the message. For the purposes of efficiency, the bank
+/// wants to be able to process multiple transactions in a single request. So the message now
+/// looks like this:
+///
+/// ```text
+/// from=#{from_id}&tx_list=#{transactions}
+/// ```
+///
+/// With the transaction list formatted like:
+///
+/// ```text
+/// to:amount(;to:amount)*
+/// ```
+///
+/// There's still a weakness here: the MAC is vulnerable to length extension attacks. How?
+///
+/// Well, the output of CBC-MAC is a valid IV for a new message.
+///
+/// "But we don't control the IV anymore!"
+///
+/// With sufficient mastery of CBC, we can fake it.
+///
+/// Your mission: capture a valid message from your target user. Use length extension to add a
+/// transfer to your account.
+#[test]
+fn challenge49() {
+    use arse::encrypt::BLOCK_SIZE;
+    use arse::mac::forge_iv;
+    use arse::mac::transfer::{append_transfer, ApiError, Client, Server, Transfer};
+    use rand::{thread_rng, RngCore};
+
+    const VICTIM: u32 = 1;
+    const ATTACKER: u32 = 2;
+
+    let mut key = [0u8; 16];
+    thread_rng().fill_bytes(&mut key);
+    let server = Server::new(&key);
+    let victim = Client::new(&key, VICTIM);
+    let attacker = Client::new(&key, ATTACKER);
+
+    // version 1: the attacker signs a transfer to themselves, then rewrites the first block and
+    // compensates in the IV
+    let signed = attacker.transfer(ATTACKER, 1_000_000);
+    let (message, rest) = signed.split_at(signed.len() - 2 * BLOCK_SIZE);
+    let (iv, mac) = rest.split_at(BLOCK_SIZE);
+
+    let forged_message = format!("from={}&to={}&amount=1000000", VICTIM, ATTACKER).into_bytes();
+    let mut tampered = forged_message.clone();
+    tampered.extend_from_slice(iv);
+    tampered.extend_from_slice(mac);
+    assert_eq!(server.handle_v1(&tampered), Err(ApiError::BadMac));
+
+    let mut forged = forged_message.clone();
+    forged.extend(forge_iv(message, iv, &forged_message).unwrap());
+    forged.extend_from_slice(mac);
+    assert_eq!(
+        server.handle_v1(&forged),
+        Ok(Transfer {
+            from: VICTIM,
+            to: ATTACKER,
+            amount: 1_000_000,
+        })
+    );
+
+    // version 2: capture a request from the victim, and glue one of our own onto it; about one
+    // capture in sixteen can't be extended, so wait for another if need be
+    let (captured, forged) = (1..=64)
+        .find_map(|amount| {
+            let captured = victim.transfer_list(&[(3, 5), (4, amount)]);
+            let forged = append_transfer(&captured, &attacker, 1_000_000)?;
+            Some((captured, forged))
+        })
+        .unwrap();
+    let mut wanted = server.handle_v2(&captured).unwrap();
+    assert_eq!(wanted.len(), 2);
+    wanted.push(Transfer {
+        from: VICTIM,
+        to: ATTACKER,
+        amount: 1_000_000,
+    });
+    assert_eq!(server.handle_v2(&forged), Ok(wanted));
+}
+
+/// Challenge 49's length extension again, with account numbers long enough that the attacker's
+/// first block is nothing but `from=N&tx_list=`.
+#[test]
+fn challenge49_long_ids() {
+    use arse::mac::transfer::{append_transfer, Client, Server, Transfer};
+    use rand::{thread_rng, RngCore};
+
+    for &(victim_id, attacker_id) in &[(1, 1000), (12345, 67890), (4_294_967_295, 1_000_000_000)] {
+        let mut key = [0u8; 16];
+        thread_rng().fill_bytes(&mut key);
+        let server = Server::new(&key);
+        let victim = Client::new(&key, victim_id);
+        let attacker = Client::new(&key, attacker_id);
+
+        let (captured, forged) = (1..=64)
+            .find_map(|amount| {
+                let captured = victim.transfer_list(&[(31_337, 5), (4, amount)]);
+                let forged = append_transfer(&captured, &attacker, 1_000_000)?;
+                Some((captured, forged))
+            })
+            .unwrap();
+        let mut wanted = server.handle_v2(&captured).unwrap();
+        wanted.push(Transfer {
+            from: victim_id,
+            to: attacker_id,
+            amount: 1_000_000,
+        });
+        assert_eq!(server.handle_v2(&forged), Ok(wanted));
+    }
+}
+
+/// # Hashing with CBC-MAC
+///
+/// Sometimes people try to use CBC-MAC as a hash function.
+///
+/// This is a bad idea. Matt Green explains:
+///
+/// > To make a long story short: cryptographic hash functions are public functions (i.e., no
+/// > secret key) that have the property of collision-resistance (it's hard to find two messages
+/// > with the same hash). MACs are keyed functions that (typically) provide message
+/// > unforgeability -- a very different property. Moreover, they guarantee this only when the key
+/// > is secret.
+///
+/// Let's try a simple exercise.
+///
+/// Hash functions are often used for code verification. This snippet of JavaScript (with newline):
+///
+/// ```text
+/// alert('MZA who was that?');
+/// ```
+///
+/// Hashes to 296b8d7cb78a243dda4d0a61d33bbdd1 under CBC-MAC with a key of "YELLOW SUBMARINE" and a
+/// 0 IV.
+///
+/// Forge a valid snippet of JavaScript that alerts "Ayo, the Wu is back!" and hashes to the same
+/// value. Ensure that it runs in a browser.
+///
+/// > Extra Credit
+/// >
+/// > Write JavaScript code that downloads your file, checks its CBC-MAC, and inserts it into the
+/// > DOM iff it matches the expected hash.
+#[test]
+fn challenge50() {
+    use arse::encode::hex::ToHex;
+    use arse::mac::{cbc_mac_fixed_iv, forge_javascript};
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+    const CODE: &[u8] = b"alert('Ayo, the Wu is back!');";
+
+    let target = cbc_mac_fixed_iv(KEY, b"alert('MZA who was that?');\n").unwrap();
+    assert_eq!((&target).to_hex(), "296b8d7cb78a243dda4d0a61d33bbdd1");
+
+    let forged = forge_javascript(KEY, CODE, &target).unwrap();
+    assert_eq!(cbc_mac_fixed_iv(KEY, &forged).unwrap(), target);
+
+    // the code runs, and everything after it is a single-line comment
+    assert!(forged.starts_with(CODE));
+    assert_eq!(&forged[CODE.len()..CODE.len() + 2], b"//");
+    assert!(!forged.iter().any(|&b| b == b'\n' || b == b'\r'));
+}
+
+/// # Compression Ratio Side-Channel Attacks
+///
+/// Internet traffic is often compressed to save bandwidth. Until recently, this included HTTPS
+/// headers, and it still includes the contents of responses.
+///
+/// Why does that matter?
+///
+/// Well, if you're an attacker with:
+///
+/// 1. Partial plaintext knowledge *and*
+/// 2. Partial plaintext control *and*
+/// 3. Access to a compression oracle
+///
+/// You've got a pretty good chance to recover any additional unknown plaintext.
+///
+/// What's a compression oracle? You give it some input and it tells you how well the full
+/// message compresses, i.e. the length of the resultant output.
+///
+/// This is somewhat similar to the timing attacks we did way back in set 4 in that we're taking
+/// advantage of incidental side channels rather than attacking the cryptographic mechanisms
+/// themselves.
+///
+/// Scenario: you are running a MITM attack with an eye towards stealing secure session cookies.
+/// You've injected malicious content allowing you to spawn arbitrary requests and observe them in
+/// flight. (The particulars aren't terribly important, just roll with it.)
+///
+/// So! Write this oracle:
+///
+/// ```text
+/// oracle(P) -> length(encrypt(compress(format_request(P))))
+/// ```
+///
+/// Format the request like this:
+///
+/// ```text
+/// POST / HTTP/1.1
+/// Host: hapless.com
+/// Cookie: sessionid=TmV2ZXIgcmV2ZWFsIHRoZSBXdS1UYW5nIFNlY3JldCE=
+/// Content-Length: ((len(P)))
+/// ((P))
+/// ```
+///
+/// (Pretend you can't see that session id. You're the attacker.)
+///
+/// Compress using zlib or whatever.
+///
+/// Encryption... is actually kind of irrelevant for our purposes, but be a sport. Just use some
+/// stream cipher. Dealer's choice. Random key/IV on every call to the oracle.
+///
+/// And then just return the length in bytes.
+///
+/// Now, the idea here is to leak information using the compression library. A payload of
+/// "sessionid=T" should compress just a little bit better than, say, "sessionid=S".
+///
+/// There is one complicating factor. The DEFLATE algorithm operates in terms of individual bits,
+/// but the final message length will be in bytes. Even if you do find a better compression, the
+/// difference may not cross a byte boundary. So that's a problem.
+///
+/// You may also get some incidental false positives.
+///
+/// To get around this, you'll need to do some work. This is an exercise in creativity, so I'm not
+/// going to give you all the secrets. Be prepared to do some experimentation.
+///
+/// Once you've got CTR, try CBC. It's harder: the block size means that a byte's savings may not
+/// show up in the ciphertext length at all; you'll need to use padding to get a byte of savings
+/// to push you over a block boundary.
+#[test]
+fn challenge51() {
+    use arse::compression::{recover_secret, CompressionOracle, Mode};
+
+    const SESSION_ID: &[u8] = b"TmV2ZXIgcmV2ZWFsIHRoZSBXdS1UYW5nIFNlY3JldCE=";
+
+    for &mode in &[Mode::Ctr, Mode::Cbc] {
+        let oracle = CompressionOracle::new(SESSION_ID, mode);
+        let recovered = recover_secret(|body| oracle.length(body), b"sessionid=", 64);
+        assert_eq!(recovered.as_deref(), Some(SESSION_ID), "{:?}", mode);
+    }
+}
+
+/// # Iterated Hash Function Multicollisions
+///
+/// While we're on the topic of hash functions...
+///
+/// The major feature you want in your hash function is collision-resistance. That is, it should
+/// be hard to generate collisions, and it should be really hard to generate a collision for a
+/// given hash (aka preimage).
+///
+/// Iterated hash functions have a problem: the effort to generate *lots* of collisions scales
+/// sublinearly.
+///
+/// What's an iterated hash function? For all intents and purposes, we're talking about the
+/// Merkle-Damgard construction. It looks like this:
+///
+/// ```text
+/// function MD(M, H, C):
+///   for M[i] in pad(M):
+///     H := C(M[i], H)
+///   return H
+/// ```
+///
+/// For message M, initial state H, and compression function C.
+///
+/// This should look really familiar, because SHA-1 and MD4 are both in this category. What's
+/// cool is you can use this formula to build a makeshift hash function out of some spare crypto
+/// primitives you have lying around (e.g. C = AES-128).
+///
+/// Back on task: the cost of collisions scales sublinearly. What does that mean? If it's
+/// feasible to find one collision, it's probably feasible to find a lot.
+///
+/// How? For a given state H, find two blocks that collide. Now take the resulting hash from this
+/// collision as your new H and repeat. Recognize that with each iteration you can actually
+/// double your collisions by subbing in either of the two blocks for that slot.
+///
+/// This means that if finding two colliding messages takes 2^(b/2) work (where b is the
+/// bit-size of the hash function), then finding 2^n colliding messages only takes n*2^(b/2)
+/// work.
+///
+/// Let's test it. First, build your own MD hash function. We're going to be generating a LOT of
+/// collisions, so don't knock yourself out. In fact, go out of your way to make it bad. Here's
+/// one way:
+///
+/// 1. Take a fast block cipher and use it as C.
+/// 2. Make H pretty small. I won't look down on you if it's only 16 bits. Pick some initial H.
+/// 3. H is going to be the input key and the output block from C. That means you'll need to pad
+///    it on the way in and drop bits on the way out.
+///
+/// Now write the function f(n) that will generate 2^n collisions in this hash function.
+///
+/// Why does this matter? Well, one reason is that people have tried to strengthen hash functions
+/// by cascading them together. Here's what I mean:
+///
+/// 1. Take hash functions f and g.
+/// 2. Build h such that h(x) = f(x) || g(x).
+///
+/// The idea is that if collisions in f cost 2^(b1/2) and collisions in g cost 2^(b2/2),
+/// collisions in h should come to the princely sum of 2^((b1+b2)/2).
+///
+/// But now we know that's not true!
+///
+/// Here's the idea:
+///
+/// 1. Pick the "cheaper" hash function. Suppose it's f.
+/// 2. Generate 2^(b2/2) colliding messages in f.
+/// 3. There's a good chance your message pool has a collision in g.
+/// 4. If not, keep generating cheap collisions until you find it.
+///
+/// Prove this out by building a more expensive (but not *too* expensive) hash function to pair
+/// with the one you just used. Find a pair of messages that collide under both functions.
+/// Measure the total number of calls to the collision function.
+#[test]
+fn challenge52() {
+    use arse::md::multicollision::{cascade_collision, Multicollision};
+    use arse::md::ToyHash;
+    use std::collections::HashSet;
+
+    let f = ToyHash::new(16);
+    let multicollision = Multicollision::generate(&f, f.iv(), 8);
+    let messages = multicollision.messages().collect::<HashSet<Vec<u8>>>();
+    assert_eq!(messages.len(), 256);
+    let digest = f.digest(&multicollision.message(0));
+    assert!(messages.iter().all(|m| f.digest(m) == digest));
+
+    let g = ToyHash::with_iv(24, 0x00c0_ffee);
+    f.reset_calls();
+    let collision = cascade_collision(&f, &g);
+    assert_ne!(collision.a, collision.b);
+    assert_eq!(f.digest(&collision.a), f.digest(&collision.b));
+    assert_eq!(g.digest(&collision.a), g.digest(&collision.b));
+
+    println!(
+        "2^{} cheap collisions, {} calls to f, {} calls to g",
+        collision.levels, collision.cheap_calls, collision.expensive_calls
+    );
+}
+
+/// # Kelsey and Schneier's Expandable Messages
+///
+/// One of the basic yardsticks we use to judge a cryptographic hash function is its resistance
+/// to second preimage attacks. That means that if I give you x and y such that H(x) = y, you
+/// should have a tough time finding x' such that H(x') = H(x).
+///
+/// How tough? Brute-force tough. For a 2^b hash function, we want second preimage attacks to
+/// cost 2^b operations.
+///
+/// It turns out this is not the case for very long messages.
+///
+/// Consider the problem we're trying to solve: we want to find a message that will collide with
+/// H(x) in the very last iteration of the compression function. But there's a lot of
+/// intermediate hash states for us to hit!
+///
+/// Suppose our message x is 2^k blocks long. Then we have 2^k intermediate states we can
+/// target. This means that finding a second preimage is about 2^k times easier than we'd like.
+///
+/// The problem is Merkle-Damgard strengthening: the length of the message is hashed in at the
+/// end, so a second preimage has to be the same length as the original.
+///
+/// Let's generate an expandable message, which can be made any length within a range while
+/// producing the same final state. Here's how:
+///
+/// 1. Starting from the hash function's initial state, find a collision between a single-block
+///    message and a message of 2^(k-1)+1 blocks. DO NOT hash the entire long message each time.
+///    Choose 2^(k-1) dummy blocks, hash those, then focus on the last block.
+/// 2. Take the output state from the first step. Use this as your new initial state and find
+///    another collision between a single-block message and a message of 2^(k-2)+1 blocks.
+/// 3. Repeat this process k total times. Your last collision should be between a single-block
+///    message and a message of 2^0+1 = 2 blocks.
+///
+/// Now you have a (k, k + 2^k - 1)-expandable message: you can produce a message of any length
+/// in that range by picking the short or long message at each step.
+///
+/// We're ready to find a second preimage. Here's how:
+///
+/// 1. Generate an expandable message of length (k, k + 2^k - 1) using the strategy outlined
+///    above.
+/// 2. Hash M and generate a map of intermediate hash states to the block indices that they
+///    correspond to.
+/// 3. From your expandable message's final state, find a single-block "bridge" to intermediate
+///    state in your map. Note the index i it maps to.
+/// 4. Use your expandable message to generate a prefix of the right length such that len(prefix
+///    || bridge || M\[i..\]) = len(M).
+///
+/// The padding in the final block should now be correct, and your forgery should hash to the
+/// same value as M.
+#[test]
+fn challenge53() {
+    use arse::encrypt::BLOCK_SIZE;
+    use arse::md::expandable::{second_preimage, ExpandableMessage};
+    use arse::md::ToyHash;
+    use rand::{thread_rng, RngCore};
+
+    let hash = ToyHash::new(24);
+
+    let expandable = ExpandableMessage::generate(&hash, hash.iv(), 4);
+    assert_eq!((expandable.min_blocks(), expandable.max_blocks()), (4, 19));
+    for blocks in 4..=19 {
+        let message = expandable.message(blocks).unwrap();
+        assert_eq!(message.len(), blocks * BLOCK_SIZE);
+        assert_eq!(hash.iterate(hash.iv(), &message), expandable.state());
+    }
+    assert_eq!(expandable.message(20), None);
+
+    for &blocks in &[1 << 10, (1 << 11) + 3] {
+        let mut target = vec![0u8; blocks * BLOCK_SIZE + 7];
+        thread_rng().fill_bytes(&mut target);
+
+        let forgery = second_preimage(&hash, &target).unwrap();
+        assert_ne!(forgery, target);
+        assert_eq!(forgery.len(), target.len());
+        assert_eq!(hash.digest(&forgery), hash.digest(&target));
+    }
+}
+
+/// # Kelsey and Kohno's Nostradamus Attack
+///
+/// Hash functions are sometimes used as proof of a secret prediction.
+///
+/// For example, suppose you wanted to predict the score of every Major League Baseball game in a
+/// season. (2,430 in all.) You might be concerned that publishing your predictions would affect
+/// the outcomes.
+///
+/// So instead you write down all the scores, hash the document, and publish the hash. Once the
+/// season is over, you publish the document. Everyone can then hash the document to verify your
+/// soothsaying prowess.
+///
+/// But what if you can't accurately predict the scores of 2.4k baseball games? Have no fear:
+/// forging a prediction under this scheme reduces to another second preimage attack.
+///
+/// We could apply the long message attack from the previous problem, but it would look pretty
+/// shady. Would you trust someone whose predicted message turned out to be 2^50 bytes long?
+///
+/// It turns out we can run a successful attack with a much shorter suffix. Check the method:
+///
+/// 1. Generate a large number of initial hash states. Say, 2^k.
+/// 2. Pair them up and generate single-block collisions. Now you have 2^k hash states that
+///    collide into 2^(k-1) states.
+/// 3. Repeat the process. Pair up the 2^(k-1) states and generate collisions. Now you're down to
+///    2^(k-2) states.
+/// 4. Keep doing this until you have one state. This is your prediction.
+/// 5. Well, sort of. You need to commit to some length to encode in the padding. Make sure it's
+///    long enough to accommodate your actual message, this suffix, and a little bit of glue to
+///    join them up. Hash this padding block using the state from step 4 - THIS is your
+///    prediction.
+///
+/// What did you just build? It's basically a funnel mapping many initial states into a common
+/// final state. What's critical is we now have a big field of 2^k states we can try to collide
+/// into, but the actual suffix we'll add is only k blocks long.
+///
+/// The next step is to generate the prediction message. Once you have the message, you can hash
+/// it and then search for a single "glue" block that collides into one of the leaves in your
+/// tree. Once you have a match, you can just trace a path down to the root, reading off the
+/// blocks as you go.
+///
+/// To implement this, you'll need to generate a diamond structure with 2^k leaves. The cost to
+/// construct the tree is roughly 2^(k/2) times the cost to find a single collision, and finding
+/// the glue block takes about 2^(b-k) tries.
+#[test]
+fn challenge54() {
+    use arse::encrypt::BLOCK_SIZE;
+    use arse::md::herding::{herd, Diamond};
+    use arse::md::ToyHash;
+
+    const K: usize = 8;
+    const THREADS: usize = 4;
+
+    let hash = ToyHash::new(20);
+    let diamond = Diamond::generate(&hash, K, THREADS);
+    assert_eq!(diamond.k(), K);
+    assert_eq!(diamond.work.len(), K);
+    for (level, calls) in diamond.work.iter().enumerate() {
+        println!(
+            "level {}: {} collisions, {} compression calls",
+            level,
+            1 << (K - 1 - level),
+            calls
+        );
+    }
+
+    let mut prediction = b"Final scores: Cubs 7, Giants 3; Red Sox 2, Yankees 1".to_vec();
+    while prediction.len() % BLOCK_SIZE != 0 {
+        prediction.push(b' ');
+    }
+    let commitment = diamond.commitment(&hash, prediction.len());
+
+    let message = herd(&hash, &diamond, &prediction, THREADS).unwrap();
+    assert!(message.starts_with(&prediction));
+    assert_eq!(message.len(), prediction.len() + (K + 1) * BLOCK_SIZE);
+    assert_eq!(hash.digest(&message), commitment);
+
+    assert_eq!(herd(&hash, &diamond, b"not a whole block", THREADS), None);
+}
+
+/// # MD4 Collisions
+///
+/// MD4 is a 128-bit cryptographic hash function, meaning it should take a work factor of
+/// roughly 2^64 to find collisions.
+///
+/// It turns out we can do much better.
+///
+/// The paper "Cryptanalysis of the Hash Functions MD4 and RIPEMD" by Wang et al details a
+/// cryptanalytic attack that lets us find collisions in 2^8 or less.
+///
+/// Given a message block M, Wang outlines a strategy for finding a sister message block M',
+/// differing only in a few bits, that will collide with it. Just so long as a short set of
+/// conditions holds true for M.
+///
+/// What sort of conditions? Simple bitwise equalities within the intermediate hash function
+/// state, e.g. a\[1\]\[6\] = b\[0\]\[6\]. This should be read as: "the sixth bit (zero-indexed) of
+/// a\[1\] (i.e. the first update to 'a') should equal the sixth bit of b\[0\] (i.e. the initial
+/// value of 'b')".
+///
+/// It turns out that a lot of these conditions are trivial to enforce. To see why, take a look
+/// at the first (of three) rounds in the MD4 compression function. In this round, we iterate
+/// over each word in the message block sequentially and mix it into the state. So we can make
+/// sure all our first-round conditions hold by doing this:
+///
+/// ```text
+/// # calculate the new value for a[1] in the normal fashion
+/// a[1] = (a[0] + f(b[0], c[0], d[0]) + m[0]).lrot(3)
+///
+/// # correct the erroneous bit
+/// a[1] ^= ((a[1][6] ^ b[0][6]) << 6)
+///
+/// # use algebra to correct the first message block
+/// m[0] = a[1].rrot(3) - a[0] - F(b[0], c[0], d[0])
+/// ```
+///
+/// Simply ensuring all the first round conditions puts us well within the range to generate
+/// collisions, but we can do better by correcting some additional conditions in the second
+/// round. This is a bit trickier, as we need to take care not to stomp on any of the first-round
+/// conditions.
+///
+/// Once you've adequately massaged M, you can simply generate M' and check for a collision.
+///
+/// ```text
+/// M' := M + (0, 2^31, 2^31 - 2^28, 0, 0, 0, 0, 0, 0, 0, 0, 0, -2^16, 0, 0, 0)
+/// ```
+///
+/// Find a collision in MD4.
+#[test]
+fn challenge55() {
+    use arse::md4::digest;
+    use arse::md4::wang::{find_collision, is_differential_pair, massage, satisfied, CONDITIONS};
+    use rand::{thread_rng, Rng};
+
+    // message modification satisfies every round one condition, whatever it starts with
+    let mut words = [0u32; 16];
+    thread_rng().fill(&mut words[..]);
+    massage(&mut words);
+    let round_one = CONDITIONS.iter().filter(|&&(step, _)| step <= 16).count();
+    assert_eq!(satisfied(&words, 16), round_one);
+
+    let collision = find_collision();
+    assert_ne!(collision.m, collision.m_prime);
+    assert!(is_differential_pair(&collision.m, &collision.m_prime));
+    assert_eq!(digest(&collision.m), digest(&collision.m_prime));
+    println!("collision after {} attempts", collision.attempts);
+}
+
+/// # RC4 Single-Byte Biases
+///
+/// RC4 is popular stream cipher notable for its usage in protocols like TLS, WPA, RDP, &c.
+///
+/// It's also susceptible to significant single-byte biases, especially early in the keystream.
+/// What does this mean?
+///
+/// Simply: for a given position in the keystream, certain bytes are more (or less) likely to pop
+/// up than others. Given enough encryptions of a given plaintext, an attacker can use these
+/// biases to recover the entire plaintext.
+///
+/// Now, search online for "On the Security of RC4 in TLS and WPA". This site is your one-stop
+/// shop for RC4 information.
+///
+/// Click through to "RC4 biases" on the right.
+///
+/// These are graphs of each single-byte bias (one per page). Notice in particular the monster
+/// spikes on z16, z32, z48, etc. (Note: these are one-indexed, so z16 = keystream\[15\].)
+///
+/// How useful are these biases?
+///
+/// Click through to the research paper and scroll down to the simulation results. (Incidentally,
+/// the whole paper is a good read if you have some spare time.) We start out with clear spikes
+/// at 2^26 iterations, but our chances for recovering each of the first 256 bytes approaches 1
+/// as we get up towards 2^32.
+///
+/// There are two ways to take advantage of these biases. The first method is really simple:
+///
+/// 1. Gain exhaustive knowledge of the keystream biases.
+/// 2. Encrypt the unknown plaintext 2^30+ times under different keys.
+/// 3. Compare the ciphertext biases against the keystream biases.
+///
+/// Doing this requires deep knowledge of the biases for each byte of the keystream. But it turns
+/// out we can do pretty well with just a few useful biases - if we have some control over the
+/// plaintext.
+///
+/// How? By using knowledge of a single bias as a peephole into the plaintext.
+///
+/// Decode this secret:
+///
+/// ```text
+/// QkUgU1VSRSBUTyBEUklOSyBZT1VSIE9WQUxUSU5F
+/// ```
+///
+/// And call it a cookie. No, don't look at it. Just decode it and hang on to it.
+///
+/// Build an encryption oracle:
+///
+/// ```text
+/// RC4(your-request || cookie, random-key)
+/// ```
+///
+/// Use a fresh 128-bit key on every invocation.
+///
+/// Picture this scenario: you want to steal a user's secure cookie. You can spawn arbitrary
+/// requests (from a malicious plugin or somesuch) and monitor network traffic. (Ok, this is
+/// unrealistic - the cookie wouldn't be right at the beginning of the request like that - this
+/// is just an example!)
+///
+/// You can control the position of the cookie by requesting "/", "/A", "/AA", and so on.
+///
+/// Build bias maps for a couple chosen indices (z16 and z32 are good) for every possible byte.
+///
+/// Run as many encryptions as you need to make the bias for those indices dominate. Then you
+/// can exploit those biases to recover the plaintext.
+///
+/// Keep in mind that this is something of a toy example: the actual TLS attack is a little
+/// more complicated, because the cookie position is fixed and the biases are spread out.
+#[test]
+fn challenge56() {
+    use arse::encode::base64::TryFromBase64;
+    use arse::rc4_bias::{recover_at, recover_cookie, CookieOracle};
+
+    let cookie = "QkUgU1VSRSBUTyBEUklOSyBZT1VSIE9WQUxUSU5F"
+        .try_from_base64()
+        .unwrap();
+    let oracle = CookieOracle::new(&cookie);
+
+    // the full attack wants 2^23 or more samples for each of sixteen padding lengths, which is
+    // far too slow for a test; a single padding length still reads one byte through each bias.
+    // The Z32 bias is the weaker of the two, and has to beat 255 other values' noise, so give it
+    // 2^25 samples to stand around nine standard deviations clear of the rest.
+    let prefix_len = 14;
+    let recovered = recover_at(&mut |request| oracle.encrypt(request), prefix_len, 1 << 25);
+    assert_eq!(
+        recovered,
+        (Some(cookie[15 - prefix_len]), Some(cookie[31 - prefix_len]))
+    );
+
+    // cookies past the Z32 position can't be read at all
+    assert_eq!(
+        recover_cookie(|request| oracle.encrypt(request), 33, 1),
+        None
+    );
+}
+
+/// The whole of challenge 56, reading every byte of the cookie. With `2^25` samples for each of
+/// sixteen padding lengths this takes the better part of a quarter of an hour, so it only runs
+/// when asked for with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn challenge56_full() {
+    use arse::encode::base64::TryFromBase64;
+    use arse::rc4_bias::{recover_cookie, CookieOracle};
+
+    let cookie = "QkUgU1VSRSBUTyBEUklOSyBZT1VSIE9WQUxUSU5F"
+        .try_from_base64()
+        .unwrap();
+    let oracle = CookieOracle::new(&cookie);
+
+    let recovered =
+        recover_cookie(|request| oracle.encrypt(request), cookie.len(), 1 << 25).unwrap();
+    println!("{}", String::from_utf8_lossy(&recovered));
+    assert_eq!(recovered, cookie);
+}
@@ -0,0 +1,715 @@
+/// # Diffie-Hellman Revisited: Small Subgroup Confinement
+///
+/// This set is going to focus on elliptic curves. But before we get to that, we're going to
+/// kick things off with some classic Diffie-Hellman.
+///
+/// Trust me, it's gonna make sense later.
+///
+/// Let's get right into it. First, build your typical Diffie-Hellman key agreement: Alice and
+/// Bob exchange public keys and derive the same shared secret. Then Bob sends Alice some message
+/// with a MAC over it. Easy peasy.
+///
+/// Use these parameters:
+///
+/// ```text
+/// p = 7199773997391911030609999317773941274322764333428698921736339643928346453700085358802973900485592910475480089726140708102474957429903531369589969318716771
+/// q = 236234353446506858198510045061214171961
+/// ```
+///
+/// (with a generator `g` of order `q`).
+///
+/// "What's that `q` parameter?" you ask. Some of the Diffie-Hellman groups we use have
+/// subgroups of smaller order. The generator `g` has order `q`, which divides `p - 1`.
+///
+/// This means that for `p - 1 = q * j`, `j` is a cofactor, and it might have small factors.
+/// Here's what we're going to do:
+///
+/// 1. Take one of the small factors `j`. Call it `r`. We want to find an element `h` of order
+///    `r`. To find it, do:
+///
+///    ```text
+///    h := rand(1, p)^((p-1)/r) mod p
+///    ```
+///
+///    If `h = 1`, try again.
+///
+/// 2. You're Eve. Send Bob `h` as your public key. Note that `h` is not a valid public key!
+///    There is no `x` such that `h = g^x mod p`. But Bob doesn't know that.
+///
+/// 3. Bob will compute:
+///
+///    ```text
+///    K := h^x mod p
+///    ```
+///
+///    Where `x` is his secret key and `K` is the output shared secret. Bob then sends back
+///    `(m, t)`, with:
+///
+///    ```text
+///    m := "crazy flamboyant for the rap enjoyment"
+///    t := MAC(K, m)
+///    ```
+///
+/// 4. We (Eve) can't compute `K`, because `h` isn't actually a valid public key. But we're not
+///    licked yet.
+///
+///    Remember how we saw that `g^x` starts repeating when `x > q`? `h` has the same property
+///    with `r`. This means there are only `r` possible values of `K` that Bob could have
+///    generated. We can recover `K` by doing a brute-force search over these values until `t =
+///    MAC(K, m)`.
+///
+///    Now we know Bob's secret key `x mod r`.
+///
+/// 5. Repeat steps 1 through 4 many times. Eventually you will know:
+///
+///    ```text
+///    x = b1 mod r1
+///    x = b2 mod r2
+///    x = b3 mod r3
+///    ...
+///    ```
+///
+///    Once `(r1*r2*...*rn) > q`, you'll have enough information to reassemble Bob's secret key
+///    using the Chinese Remainder Theorem.
+#[test]
+fn challenge57() {
+    use arse::dh::small_subgroup::{recover_key, small_order_elements, Bob};
+    use arse::dh::Group;
+    use num_traits::One;
+
+    let group = Group::challenge57();
+    assert!(group.g.modpow(&group.q, &group.p).is_one());
+
+    let x = group.generate_private();
+    let bob = Bob::new(group.clone(), x.clone());
+    assert_eq!(bob.public_key(), group.public(&x));
+
+    let mut log = Vec::new();
+    let recovered = recover_key(
+        &group,
+        small_order_elements(&group),
+        |h| bob.respond(h),
+        |residue| {
+            println!("{}", residue);
+            log.push(residue.clone());
+        },
+    );
+    assert_eq!(recovered, Some(x.clone()));
+    assert!(log
+        .iter()
+        .all(|residue| &x % residue.modulus == residue.value.into()));
+}
+
+/// # Pollard's Method for Catching Kangaroos
+///
+/// The last problem was a little contrived. It only worked because I helpfully foisted those
+/// broken group parameters on Alice and Bob. While real-world groups may include some small
+/// subgroups, it's improbable to find this many in a randomly generated group.
+///
+/// So what if we can only recover some fraction of the Bob's secret key? It feels like there
+/// should be some way to use that knowledge to recover the rest. And there is: Pollard's
+/// kangaroo algorithm.
+///
+/// This is a generic attack for computing a discrete logarithm (or "index") known to lie
+/// within a certain contiguous range \[a, b\]. It has a work factor approximately the square
+/// root of the size of the range.
+///
+/// The basic strategy is to try to find a collision between two pseudorandom sequences of
+/// elements. One will start from an element of known index, and one will start from the
+/// element y whose index we want to find.
+///
+/// The first sequence (the "tame kangaroo") makes N jumps from g^b, where each jump's size is
+/// chosen by a pseudorandom function f of the element it lands on, and remembers where it
+/// stopped and how far it went. The second sequence (the "wild kangaroo") starts from y and
+/// jumps by the same rule. If it ever lands on the same element as the tame kangaroo, it will
+/// follow the same path from then on, and we'll catch it at the tame kangaroo's final
+/// position; if it travels further than the tame kangaroo could have without being caught, we
+/// give up.
+///
+/// Implement Pollard's kangaroo algorithm, with these parameters:
+///
+/// ```text
+/// p = 11470374874925275658116663507232161402086650258453896274534991676898999262641581519101074740642369848233294239851519212341844337347119899874391456329785623
+/// q = 335062023296420808191071248367701059461
+/// j = 34233586850807404623475048381328686211071196701374230492615844865929237417097514638999377942356150481334217896204702
+/// g = 622952335333961296978159266084741085889881358738459939978290179936063635566740258555167783009058567397963466103140082647486611657350811560630587013183357
+/// ```
+///
+/// And use it to find the index of:
+///
+/// ```text
+/// y = 7760073848032689505395005705677365876654629189298052775754597607446617558600394076764814236081991643094239886772481052254010323780165093955236429914607119
+/// ```
+///
+/// in the range \[0, 2^20\], and of:
+///
+/// ```text
+/// y = 9388897478013399550694114614498790691034187453089355259602614074132918843899833277397448144245883225611726912025846772975325932794909655215329941809013733
+/// ```
+///
+/// in the range \[0, 2^40\].
+///
+/// Now for the fun part: use the small subgroups of j to recover Bob's key modulo their
+/// product r, then use the kangaroo algorithm to find the rest. Since x = n mod r, we can write
+/// x = n + m*r, and so y * g^-n = (g^r)^m, where m is in \[0, (q-1)/r\].
+#[test]
+fn challenge58() {
+    use arse::dh::small_subgroup::{recover_key_with_kangaroo, Bob};
+    use arse::dh::Group;
+    use arse::kangaroo::catch;
+    use num_bigint::BigUint;
+    use num_traits::{Num, Zero};
+
+    let group = Group::challenge58();
+    for &(y, bits) in &[
+        (
+            "7760073848032689505395005705677365876654629189298052775754597607446617558600394076\
+             764814236081991643094239886772481052254010323780165093955236429914607119",
+            20,
+        ),
+        (
+            "9388897478013399550694114614498790691034187453089355259602614074132918843899833277\
+             397448144245883225611726912025846772975325932794909655215329941809013733",
+            40,
+        ),
+    ] {
+        let y = BigUint::from_str_radix(y, 10).unwrap();
+        let b = BigUint::from(1u64 << bits);
+        let x = catch(&group, &group.g, &y, &BigUint::zero(), &b).unwrap();
+        println!("index in [0, 2^{}]: {}", bits, x);
+        assert_eq!(group.public(&x), y);
+    }
+
+    let x = group.generate_private();
+    let bob = Bob::new(group.clone(), x.clone());
+    let recovered = recover_key_with_kangaroo(
+        &group,
+        &bob.public_key(),
+        |h| bob.respond(h),
+        |residue| println!("{}", residue),
+    );
+    assert_eq!(recovered, Some(x));
+}
+
+/// # Key-Recovery Attacks on ECDH with Invalid Curve Points
+///
+/// Now we're going to take a look at an elliptic curve (EC) attack analogous to the small
+/// subgroup attack we just did. We'll use this curve:
+///
+/// ```text
+/// y^2 = x^3 - 95051*x + 11279326
+/// ```
+///
+/// over GF(233970423115425145524320034830162017933), with the base point (182,
+/// 85518893674295321206118380980485522083) of order 29246302889428143187362802287225875743.
+///
+/// Implement affine point addition, doubling and scalar multiplication, and ECDH with Alice and
+/// Bob agreeing on a shared secret.
+///
+/// Notice that the addition and doubling formulas never use the b coefficient of the curve. So
+/// if Bob doesn't check that Alice's public key is on the curve, he'll happily compute with
+/// points on any curve sharing a and p. Some of those curves have small subgroups:
+///
+/// ```text
+/// y^2 = x^3 - 95051*x + 210
+/// y^2 = x^3 - 95051*x + 504
+/// y^2 = x^3 - 95051*x + 727
+/// ```
+///
+/// with orders:
+///
+/// ```text
+/// 233970423115425145550826547352470124412
+/// 233970423115425145544350131142039591210
+/// 233970423115425145545378039958152057148
+/// ```
+///
+/// Find points of small order on these curves, send them to Bob, and brute-force his key modulo
+/// each order from the MACs he sends back. Then combine the residues with the CRT, just like
+/// before.
+#[test]
+fn challenge59() {
+    use arse::dh::small_subgroup::{recover_key, Bob};
+    use arse::ec::invalid_curve::{challenge59_bogus_curves, small_order_points};
+    use arse::ec::Curve;
+
+    let curve = Curve::challenge59();
+    assert!(curve.contains(&curve.g));
+    assert_eq!(
+        curve.multiply(&curve.g, &curve.n),
+        arse::ec::Point::Infinity
+    );
+
+    // plain ECDH works, and a checking implementation refuses points on the wrong curve
+    let (a, b) = (curve.generate_private(), curve.generate_private());
+    let (pub_a, pub_b) = (curve.public(&a), curve.public(&b));
+    assert_eq!(
+        curve.shared_secret(&a, &pub_b),
+        curve.shared_secret(&b, &pub_a)
+    );
+    let bogus_curves = challenge59_bogus_curves(&curve);
+    let bogus = bogus_curves[0].random_point();
+    assert!(!curve.contains(&bogus));
+    assert_eq!(curve.checked_shared_secret(&b, &bogus), None);
+
+    let x = curve.generate_private();
+    let bob = Bob::new(curve.clone(), x.clone());
+    let recovered = recover_key(
+        &curve,
+        small_order_points(&bogus_curves),
+        |h| bob.respond(h),
+        |residue| println!("{}", residue),
+    );
+    assert_eq!(recovered, Some(x));
+}
+
+/// # Single-Coordinate Ladders and Insecure Twists
+///
+/// All our hard work is about to pay some dividends. Here's a list of cool-kids jargon you'll be
+/// able to deploy after completing this challenge: Montgomery curve, single-coordinate ladder,
+/// isomorphism, birational equivalence, quadratic twist, trace of Frobenius.
+///
+/// Our curve from the last challenge can be written in Montgomery form:
+///
+/// ```text
+/// v^2 = u^3 + 534*u^2 + u
+/// ```
+///
+/// with the base point (4, 85518893674295321206118380980485522083). Map between the two forms
+/// with u = x - 178 and v = y.
+///
+/// Implement the Montgomery ladder, which computes the u coordinate of a scalar multiple from
+/// nothing but the u coordinate of the point, and check that ladder(4, n) = 0.
+///
+/// Now, Bob only sees u coordinates, so there's no b to mess with. But every u which isn't on
+/// the curve is on its quadratic twist, whose order is 2*p + 2 minus the curve's:
+///
+/// ```text
+/// 233970423115425145549737651362517029924 = 2^2 * 11 * 107 * 197 * 1621 * 105143 * 405373 *
+///                                           2323367 * 1571528514013
+/// ```
+///
+/// Find points of small order on the twist, send them to Bob, and recover his key modulo each
+/// factor. Each residue is ambiguous up to sign, since kP and -kP have the same u coordinate.
+/// Resolve the ambiguity, then use the kangaroo to find the rest of the key.
+#[test]
+fn challenge60() {
+    use arse::ec::montgomery::MontgomeryCurve;
+    use arse::ec::twist::{recover_key, recover_residues, resolve_signs, Bob};
+    use arse::ec::{Curve, Point};
+    use num_traits::Zero;
+
+    let curve = MontgomeryCurve::challenge60();
+    let weierstrass = curve.to_weierstrass();
+    assert_eq!(weierstrass, Curve::challenge59());
+    assert!(curve.ladder(&curve.base_u(), &curve.n).is_zero());
+
+    // the ladder agrees with the Weierstrass arithmetic
+    let x = curve.generate_private();
+    match curve.from_weierstrass_point(&weierstrass.public(&x)) {
+        Point::Affine(u, _) => assert_eq!(u, curve.public(&x)),
+        Point::Infinity => unreachable!("x is less than the base point's order"),
+    }
+
+    // and doesn't drop the bits of keys wider than p
+    let wide = &x + (&curve.n << curve.p.bits());
+    assert_eq!(curve.ladder(&curve.base_u(), &wide), curve.public(&x));
+
+    // a Bob who checks that public keys are on the curve gives nothing away
+    let careful = Bob::new(curve.clone(), x.clone(), true);
+    let public = careful.public_key();
+    assert_eq!(
+        recover_key(&curve, &public, |u| careful.respond(u), |_| ()),
+        None
+    );
+
+    // one who doesn't gives away his key modulo the small factors of the twist's order, up to
+    // sign; finishing the job with the kangaroo is left to challenge60_kangaroo
+    let bob = Bob::new(curve.clone(), x.clone(), false);
+    let mut oracle = |u: &_| bob.respond(u);
+    let residues = recover_residues(&curve, &mut oracle, |residue| {
+        println!("{} (up to sign)", residue)
+    })
+    .unwrap();
+    let (n, m) = resolve_signs(&curve, &residues, &mut oracle).unwrap();
+    println!("x = ±{} mod {}", n, m);
+    assert!(n == &x % &m || n == (&m - &x % &m) % &m);
+}
+
+/// The whole of challenge 60, kangaroo and all. The kangaroo has about `2^40` candidates left to
+/// search, which takes minutes rather than seconds, so this only runs when asked for with
+/// `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn challenge60_kangaroo() {
+    use arse::ec::montgomery::MontgomeryCurve;
+    use arse::ec::twist::{recover_key, Bob};
+
+    let curve = MontgomeryCurve::challenge60();
+    let x = curve.generate_private();
+    let bob = Bob::new(curve.clone(), x.clone(), false);
+    let public = bob.public_key();
+    let recovered = recover_key(
+        &curve,
+        &public,
+        |u| bob.respond(u),
+        |residue| println!("{} (up to sign)", residue),
+    )
+    .unwrap();
+    println!("recovered {}", recovered);
+    assert!(recovered == x || recovered == &curve.n - &x);
+    assert_eq!(curve.public(&recovered), public);
+}
+
+/// # Duplicate-Signature Key Selection in ECDSA (and RSA)
+///
+/// Suppose you have a message-signature pair. If I give you a public key that verifies the
+/// signature, can you trust that I'm the author?
+///
+/// You shouldn't. It turns out to be pretty easy to solve this problem for ECDSA and RSA.
+///
+/// Implement ECDSA. Given a signature (r, s) on a message m under Alice's public key Q, pick a
+/// random d', compute R = u1*G + u2*Q, t = u1 + u2*d', G' = t^-1 * R, and Q' = d'*G'. Then the
+/// same signature verifies under the key (G', Q').
+///
+/// RSA is a bit harder. Given a signature s on the padded message pad(m), find primes p and q
+/// for which p-1 and q-1 are smooth and s is a generator, solve s^e' = pad(m) mod p and mod q
+/// with Pohlig-Hellman, combine the results with the CRT, and derive d' from e' as usual. Make
+/// sure N' = p*q is big enough for the signature to fit.
+#[test]
+fn challenge61() {
+    use arse::dsks::{ecdsa_key, rsa_key};
+    use arse::ec::Curve;
+    use arse::ecdsa::generate_keypair;
+    use arse::rsa;
+    use arse::rsa::signature::{sign, verify, HashAlgorithm};
+    use num_bigint::BigUint;
+
+    let message = b"hi mom";
+
+    let (alice, alice_private) = generate_keypair(&Curve::challenge59());
+    let sig = alice_private.sign(message);
+    assert!(alice.verify(message, &sig));
+    let eve = ecdsa_key(&alice, message, &sig).unwrap().public_key();
+    assert_ne!(eve, alice);
+    assert!(eve.verify(message, &sig));
+    assert!(!eve.verify(b"hi dad", &sig));
+
+    let (alice, alice_private) = rsa::generate_keypair(1024, &BigUint::from(65537u32));
+    let signature = sign(&alice_private, HashAlgorithm::Sha256, message).unwrap();
+    assert_eq!(
+        verify(&alice, HashAlgorithm::Sha256, message, &signature),
+        Ok(())
+    );
+    let (eve, eve_private) = rsa_key(&alice, HashAlgorithm::Sha256, message, &signature).unwrap();
+    println!("e' = {}", eve.e);
+    assert_ne!(eve, alice);
+    assert_eq!(
+        verify(&eve, HashAlgorithm::Sha256, message, &signature),
+        Ok(())
+    );
+    assert_eq!(
+        sign(&eve_private, HashAlgorithm::Sha256, message).unwrap(),
+        signature
+    );
+}
+
+/// # Key-Recovery Attacks on ECDSA with Biased Nonces
+///
+/// Back in set 6 we saw how "nonce" is kind of a misnomer for the k value in DSA. It's really
+/// more like an ephemeral key. And distressingly, the security of your long-term private key
+/// relies on it.
+///
+/// Nonce disclosure? Congrats, you just coughed up your secret key. Predictable nonce? Ditto.
+/// Even by repeating a nonce you lose everything. How far can we take this? Turns out, pretty
+/// far: even a slight bias in nonce generation is enough for an attacker to recover your private
+/// key.
+///
+/// Sign a bunch of messages with nonces whose low 8 bits are zero. Each signature gives
+///
+/// ```text
+/// d*t - u = b (mod q)
+/// ```
+///
+/// with t = r / (s*2^l), u = -H(m) / (s*2^l), and b < q/2^l. Build the lattice
+///
+/// ```text
+/// b1 = [  q   0   0   0   0   0 ... 0   0 ]
+/// b2 = [  0   q   0   0   0   0 ... 0   0 ]
+///                      ...
+/// bn = [  0   0   0   0   0   0 ... q   0 ]
+/// bt = [ t1  t2  t3  t4  t5  t6 ... ct  0 ]
+/// bu = [ u1  u2  u3  u4  u5  u6 ... 0  cu ]
+/// ```
+///
+/// with ct = 1/2^l and cu = q/2^l, reduce it with LLL, and look for a row ending in cu. Its
+/// second last coordinate is d*ct.
+#[test]
+fn challenge62() {
+    use arse::ec::Curve;
+    use arse::ecdsa::biased_nonce::{recover_key, sign_biased};
+    use arse::ecdsa::{generate_keypair, hash_message};
+    use arse::lattice::{default_delta, dot, gram_schmidt, lll};
+    use num_bigint::BigInt;
+    use num_rational::BigRational;
+    use num_traits::Zero;
+
+    // LLL on the textbook example
+    let basis = [[1, 1, 1], [-1, 0, 2], [3, 5, 6]]
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&x| BigRational::from_integer(BigInt::from(x)))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let reduced = lll(&basis, &default_delta());
+    let expected = [[0, 1, 0], [1, 0, 1], [-1, 0, 2]]
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&x| BigRational::from_integer(BigInt::from(x)))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(reduced, expected);
+    let (orthogonal, _) = gram_schmidt(&reduced);
+    assert!(dot(&orthogonal[0], &orthogonal[2]).is_zero());
+
+    let curve = Curve::challenge59();
+    let (public, private) = generate_keypair(&curve);
+    let signatures = (0..20)
+        .map(|i| {
+            let h = hash_message(format!("message {}", i).as_bytes(), &curve.n);
+            let sig = sign_biased(&private, &h, 8);
+            (h, sig)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(recover_key(&public, &signatures, 8), Some(private.d));
+}
+
+/// # GCM, GHASH, and the Forbidden Attack
+///
+/// GCM is the most widely deployed block cipher mode for authenticated encryption with
+/// associated data (AEAD). It's basically just CTR mode with a weird MAC function wrapped around
+/// it. The MAC function works by evaluating a polynomial over GF(2^128).
+///
+/// Remember how much trouble a repeated nonce causes for CTR mode encryption? The same thing is
+/// true here: an attacker can XOR ciphertexts together and recover plaintext using statistical
+/// methods.
+///
+/// But there's an even more devastating consequence for GCM: it immediately allows the
+/// attacker to recover the authentication key and forge arbitrary messages!
+///
+/// GCM encrypts the message with CTR mode, with the counter block `J0 = nonce || 0^31 || 1` kept
+/// aside, and authenticates it with GHASH: split the associated data and the ciphertext into
+/// blocks, append a block holding their lengths, and evaluate
+///
+/// ```text
+/// t = b1*h^n + b2*h^(n-1) + ... + bn*h + s
+/// ```
+///
+/// where `h = E(K, 0)` is the authentication key and `s = E(K, J0)` is a mask.
+///
+/// Implement GF(2^128) arithmetic (in GCM's weird bit order), GHASH, and GCM. Then implement
+/// polynomial arithmetic over GF(2^128): addition, multiplication, division with remainder, the
+/// GCD, and factorization - square-free factorization, distinct-degree factorization, and
+/// equal-degree factorization.
+///
+/// Now, the attack. Take two messages encrypted under the same key and nonce. Their tags are
+///
+/// ```text
+/// t1 = g1(h) + s
+/// t2 = g2(h) + s
+/// ```
+///
+/// so `h` is a root of `g1(x) + t1 + g2(x) + t2`, in which `s` has cancelled out. Factor it, take
+/// the degree-one factors, and you have a short list of candidates for `h`. Each one gives a
+/// candidate `s`, and you can test them by forging a tag for a message of your choosing and
+/// seeing whether it's accepted.
+#[test]
+fn challenge63() {
+    use arse::gcm::forbidden::{candidate_keys, forge, recover_key};
+    use arse::gcm::Gcm;
+    use arse::gf128::poly::Poly;
+    use arse::gf128::Gf128;
+    use rand::{random, thread_rng, RngCore};
+
+    // factoring (x + a)^2 (x + b) q(x) with q an irreducible quadratic
+    let (a, b) = (Gf128(random()), Gf128(random()));
+    let linear = |r: Gf128| Poly::new(vec![r, Gf128::ONE]);
+    let trace = |c: Gf128| (0..128).fold(Gf128::ZERO, |acc, i| acc + c.pow2k(i));
+    // x^2 + x + c is irreducible exactly when c has trace 1
+    let quadratic = loop {
+        let c = Gf128(random());
+        if trace(c) == Gf128::ONE {
+            break Poly::new(vec![c, Gf128::ONE, Gf128::ONE]);
+        }
+    };
+    let square_free = &(&linear(b) * &quadratic) * &linear(a);
+    let f = &square_free * &linear(a);
+    let factors = f.square_free_factorization();
+    let product = factors.iter().fold(Poly::one(), |acc, (g, i)| {
+        (0..*i).fold(acc, |acc, _| &acc * g)
+    });
+    assert_eq!(product, f);
+    assert!(factors.contains(&(linear(a), 2)));
+
+    let mut by_degree = square_free.distinct_degree_factorization();
+    by_degree.sort_by_key(|(_, d)| *d);
+    assert_eq!(
+        by_degree,
+        vec![(&linear(a) * &linear(b), 1), (quadratic.clone(), 2)]
+    );
+    let mut roots = f.roots();
+    roots.sort_by_key(|r| r.0);
+    let mut expected = vec![a, b];
+    expected.sort_by_key(|r| r.0);
+    assert_eq!(roots, expected);
+
+    // the attack
+    let mut key = [0u8; 16];
+    thread_rng().fill_bytes(&mut key);
+    let gcm = Gcm::new(&key).unwrap();
+    let nonce = random::<[u8; 12]>();
+    let plaintext = b"Transfer $10 to Eve's account, reference 0001";
+    let first = gcm.seal(&nonce, b"from: alice", plaintext);
+    let second = gcm.seal(
+        &nonce,
+        b"from: bob",
+        b"A different message, under the same nonce",
+    );
+
+    assert!(candidate_keys(&first, &second).contains(&gcm.auth_key()));
+    let h = recover_key(&first, &second, |sealed| gcm.open(&nonce, sealed).is_ok()).unwrap();
+    assert_eq!(h, gcm.auth_key());
+
+    // flip the amount with the known plaintext and tag the result
+    let mut ciphertext = first.ciphertext.clone();
+    ciphertext[10] ^= b'1' ^ b'9';
+    ciphertext[11] ^= b'0' ^ b'9';
+    let forged = forge(h, &first, b"from: alice", &ciphertext);
+    assert_eq!(
+        gcm.open(&nonce, &forged).unwrap(),
+        b"Transfer $99 to Eve's account, reference 0001".to_vec()
+    );
+}
+
+/// # Key-Recovery Attacks on GCM with Repeated Nonces
+///
+/// Actually, the attack in this challenge doesn't need a repeated nonce at all. It works against
+/// GCM with truncated tags, and it's due to Niels Ferguson.
+///
+/// Say the tag is truncated to 32 bits, and the message is 2^17 blocks long. Here's the key
+/// insight: squaring is linear in GF(2^128). That is,
+///
+/// ```text
+/// (a + b)^2 = a^2 + b^2
+/// ```
+///
+/// So we can represent squaring as a 128x128 matrix `Ms` over GF(2), and multiplication by a
+/// constant `c` as a matrix `Mc`. Now consider the blocks of the ciphertext multiplied by `h^2`,
+/// `h^4`, `h^8`, ..., `h^(2^17)`. If we flip bits in those blocks by the differences `d_i`, the
+/// tag changes by
+///
+/// ```text
+/// e = sum(d_i * h^(2^i)) = sum(Mdi * Ms^i) * h = Ad * h
+/// ```
+///
+/// `Ad` is linear in the bits of the `d_i`, so we can build a dependency matrix `T` with a column
+/// for each bit of the `d_i` and a row for each bit in the first few rows of `Ad`, and find
+/// nonzero solutions in its kernel: those are the changes that leave the first few bits of the
+/// tag alone, whatever `h` is. With 17 blocks to play with we can zero 16 rows, so a random such
+/// forgery is accepted with probability 2^-16.
+///
+/// When one is accepted, every other row of `Ad` must have been orthogonal to `h` too, which
+/// gives us 16 new linear equations in `h`. Collect them in a matrix `K`; `h` is in its kernel.
+/// Take a basis `X` of that kernel, and from now on work with `Ad * X` instead of `Ad`. It has
+/// fewer columns, so it takes fewer bits of `d` to zero out each row, and we can zero more rows
+/// at a time. Each forgery gets likelier than the last, until the kernel of `K` is one vector,
+/// and that's `h`.
+#[test]
+fn challenge64() {
+    use arse::gcm::forbidden::forge;
+    use arse::gcm::truncated::{
+        dependency_matrix, error_matrix, recover_key, squaring_powers, Oracle,
+    };
+    use arse::gcm::Gcm;
+    use arse::gf128::Gf128;
+    use arse::gf2::BitMatrix;
+    use rand::random;
+
+    // on a short message, the tag changes by Ad * h
+    let gcm = Gcm::new(&random::<[u8; 16]>()).unwrap();
+    let nonce = random::<[u8; 12]>();
+    let blocks = 15;
+    let sealed = gcm.seal(&nonce, &[], &[0; 15 * 16]);
+    let squarings = squaring_powers(4);
+    let d = (0..4).map(|_| Gf128(random())).collect::<Vec<_>>();
+    let mut ciphertext = sealed.ciphertext.clone();
+    for (i, d) in d.iter().enumerate() {
+        let index = blocks + 1 - (1 << (i + 1));
+        for (c, d) in ciphertext[index * 16..].iter_mut().zip(d.to_block().iter()) {
+            *c ^= d;
+        }
+    }
+    let error =
+        Gf128::from_block(&gcm.tag(&nonce, &[], &ciphertext)) + Gf128::from_block(&sealed.tag);
+    let ad = error_matrix(&d, &squarings);
+    assert_eq!(
+        Gf128::from_bits(&ad.mul_vec(&gcm.auth_key().to_bits())),
+        error
+    );
+
+    // and a change from the kernel of the dependency matrix zeroes the first rows of Ad
+    let t = dependency_matrix(3, &BitMatrix::identity(128), &squarings);
+    let change = &t.kernel()[0];
+    let d = change
+        .words()
+        .chunks(2)
+        .map(|w| Gf128(u128::from(w[0]) | u128::from(w[1]) << 64))
+        .collect::<Vec<_>>();
+    let ad = error_matrix(&d, &squarings);
+    assert!(ad.rows()[..3].iter().all(|row| row.is_zero()));
+
+    // the attack, on tags and messages small enough to be quick; challenge64_full has the
+    // challenge's own
+    let oracle = Oracle::new(2);
+    let sealed = oracle.seal(&[0; (1 << 9) * 16]);
+    assert!(oracle.verify(&sealed));
+    let mut queries = 0;
+    let h = recover_key(&sealed, 2, |forged| {
+        queries += 1;
+        oracle.verify(forged)
+    })
+    .unwrap();
+    println!("recovered h after {} queries", queries);
+    assert_eq!(h, oracle.auth_key());
+
+    let forged = forge(h, &sealed, &[], b"any message at all");
+    assert!(oracle.verify(&forged));
+}
+
+/// Challenge 64's attack on 32-bit tags and `2^17`-block messages. The first forgery alone takes
+/// about `2^16` queries, each tagging the whole message, so this only runs when asked for with
+/// `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn challenge64_full() {
+    use arse::gcm::forbidden::forge;
+    use arse::gcm::truncated::{recover_key, Oracle, MESSAGE_BLOCKS, TAG_BYTES};
+
+    let oracle = Oracle::new(TAG_BYTES);
+    let sealed = oracle.seal(&vec![0; MESSAGE_BLOCKS * 16]);
+    let mut queries = 0;
+    let h = recover_key(&sealed, TAG_BYTES, |forged| {
+        queries += 1;
+        oracle.verify(forged)
+    })
+    .unwrap();
+    println!("recovered h after {} queries", queries);
+    assert_eq!(h, oracle.auth_key());
+
+    let forged = forge(h, &sealed, &[], b"any message at all");
+    assert!(oracle.verify(&forged));
+}